@@ -0,0 +1,197 @@
+use static_init::dynamic;
+
+use crate::{log, util::{dat, error::GameError}};
+
+use super::{item::get_item_by_id, progress::Progression, state::GameState};
+
+#[dynamic]
+pub static ACHIEVEMENTS: Vec<Achievement> = get_all_achievements().unwrap_or_else(|e| {
+  log!("Failed to load achievements: {}", e);
+  Vec::new()
+});
+
+/// Something that happened in a game that achievements might care about. Pushed onto GameState::events
+/// and counted next time achievements are evaluated
+#[derive(Debug, Clone)]
+pub enum GameEvent {
+  Traded { item_id: u32, amount: i32, buying: bool },
+  Arrived(i32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+  /// Lifetime trades
+  Trades(i32),
+  /// Lifetime units of contraband sold
+  Smuggled(i32),
+  /// Credits held at once
+  Money(i32),
+  /// Crew aboard at once
+  Crew(i32),
+  /// Landed on every planet in the system
+  VisitedAll,
+}
+
+impl Condition {
+  fn parse(s: &str) -> Option<Condition> {
+    if s == "VISITED_ALL" {
+      return Some(Condition::VisitedAll);
+    }
+
+    let (name, value) = s.split_once(':')?;
+    let value = value.parse().ok()?;
+
+    match name {
+      "TRADES" => Some(Condition::Trades(value)),
+      "SMUGGLED" => Some(Condition::Smuggled(value)),
+      "MONEY" => Some(Condition::Money(value)),
+      "CREW" => Some(Condition::Crew(value)),
+      _ => None,
+    }
+  }
+
+  pub fn met(&self, progress: &Progression, state: &GameState) -> bool {
+    match self {
+      Condition::Trades(trades) => progress.trades >= *trades,
+      Condition::Smuggled(units) => progress.smuggled >= *units,
+      Condition::Money(money) => state.player.money >= *money,
+      Condition::Crew(crew) => state.player.ship.crew.len() as i32 >= *crew,
+      Condition::VisitedAll => (0..state.planets.len() as i32)
+        .all(|planet| planet == state.current_planet || state.visited.contains(&planet)),
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct Achievement {
+  pub id: i32,
+  /// Language keys
+  pub title: String,
+  pub description: String,
+  pub condition: Condition,
+}
+
+/// Counts up whatever happened since the last call, then unlocks every achievement whose condition is now met.
+/// Returns the newly unlocked ones
+pub fn evaluate(progress: &mut Progression, state: &mut GameState) -> Vec<&'static Achievement> {
+  for event in state.events.drain(..) {
+    match event {
+      GameEvent::Traded { item_id, amount, buying } => {
+        progress.trades += 1;
+
+        if !buying && get_item_by_id(item_id as i32).is_some_and(|item| item.illegal) {
+          progress.smuggled += amount;
+        }
+      },
+      GameEvent::Arrived(planet) => {
+        if !state.visited.contains(&planet) {
+          state.visited.push(planet);
+        }
+      },
+    }
+  }
+
+  let mut unlocked = Vec::new();
+
+  for achievement in ACHIEVEMENTS.iter() {
+    if !progress.has_achievement(achievement.id) && achievement.condition.met(progress, state) {
+      log!("Unlocked achievement {}", achievement.id);
+
+      progress.unlock(achievement.id);
+      unlocked.push(achievement);
+    }
+  }
+
+  unlocked
+}
+
+pub fn get_all_achievements() -> Result<Vec<Achievement>, GameError> {
+  // Laid out as follows:
+  // ID TITLE_KEY DESCRIPTION_KEY CONDITION
+  dat::read("achievements.dat")?
+    .iter()
+    .map(|record| {
+      let id = record.parse(0, "id")?;
+      let title = record.text(1, "title key")?.to_string();
+      let description = record.text(2, "description key")?.to_string();
+      let condition = record.text(3, "condition")?;
+      let condition = Condition::parse(condition).ok_or_else(|| record.field_error(3, format!("Invalid condition: {}", condition)))?;
+
+      Ok(Achievement {
+        id,
+        title,
+        description,
+        condition,
+      })
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::components::{item::get_item_by_name, progress::Progression, ship::{sorted_ships, test_ship}, state::GameState};
+
+  use super::{evaluate, get_all_achievements, GameEvent};
+
+  fn test_state() -> GameState {
+    GameState::new("Test".to_string(), test_ship(), "TEST_SEED".to_string())
+  }
+
+  #[test]
+  fn achievements_dat_parses_and_covers_ship_unlocks() {
+    let achievements = get_all_achievements().unwrap();
+
+    for unlock in sorted_ships().iter().filter_map(|ship| ship.unlock) {
+      assert!(achievements.iter().any(|achievement| achievement.id == unlock), "No achievement {}", unlock);
+    }
+  }
+
+  #[test]
+  fn trading_unlocks_once() {
+    let mut progress = Progression::default();
+    let mut state = test_state();
+
+    assert!(evaluate(&mut progress, &mut state).is_empty());
+
+    state.events.push(GameEvent::Traded { item_id: 0, amount: 1, buying: true });
+    let unlocked = evaluate(&mut progress, &mut state);
+
+    assert_eq!(unlocked.len(), 1);
+    assert_eq!(progress.trades, 1);
+
+    state.events.push(GameEvent::Traded { item_id: 0, amount: 1, buying: true });
+    assert!(evaluate(&mut progress, &mut state).is_empty());
+  }
+
+  #[test]
+  fn only_selling_contraband_counts_as_smuggling() {
+    let drugs = get_item_by_name("DRUGS").unwrap().id;
+    let grain = get_item_by_name("GRAIN").unwrap().id;
+    let mut progress = Progression::default();
+    let mut state = test_state();
+
+    state.events.push(GameEvent::Traded { item_id: drugs, amount: 10, buying: true });
+    state.events.push(GameEvent::Traded { item_id: grain, amount: 10, buying: false });
+    state.events.push(GameEvent::Traded { item_id: drugs, amount: 5, buying: false });
+    evaluate(&mut progress, &mut state);
+
+    assert_eq!(progress.smuggled, 5);
+  }
+
+  #[test]
+  fn visiting_every_planet() {
+    let mut progress = Progression::default();
+    let mut state = test_state();
+
+    for planet in 1..state.planets.len() as i32 - 1 {
+      state.events.push(GameEvent::Arrived(planet));
+    }
+
+    evaluate(&mut progress, &mut state);
+    assert!(!progress.has_achievement(2));
+
+    state.events.push(GameEvent::Arrived(state.planets.len() as i32 - 1));
+    evaluate(&mut progress, &mut state);
+    assert!(progress.has_achievement(2));
+  }
+}
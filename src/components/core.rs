@@ -1,4 +1,4 @@
-use crate::states::Stage;
+use crate::{states::Stage, util::config::Config};
 
 use super::{item::Item, progress::Progression, state::GameState};
 
@@ -7,9 +7,14 @@ use super::{item::Item, progress::Progression, state::GameState};
 pub struct Core {
   pub language: Option<serde_json::Value>,
   pub language_file: String,
+  /// English, for keys the selected language is missing. None when English is the selected language
+  pub fallback_language: Option<serde_json::Value>,
+  pub config: Config,
   pub version: String,
   pub errors: Vec<String>,
   pub state: Option<GameState>,
+  /// Slot the current game is saved to, set on new game or when loading
+  pub save_slot: Option<String>,
   pub progress: Option<Progression>,
   pub items: Vec<Item>,
   pub current_stage: Stage,
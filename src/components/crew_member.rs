@@ -1,6 +1,44 @@
+use rand::Rng;
 use serde::{Serialize, Deserialize};
 
-#[derive(Serialize, Deserialize)]
+use crate::util::{error::GameError, generators::{generate_name, NameGenerationParams}, language::{Arg, Message}};
+
+/// Job strength added per tier of a crew member, two tier 5 crew in the same job max it out
+static STRENGTH_PER_TIER: f32 = 0.1;
+/// Salary per payday for each tier, before the job multiplier
+static SALARY_PER_TIER: i32 = 20;
+/// Chance out of 100 of a candidate being each tier, tier 1 first
+static TIER_WEIGHTS: [i32; 5] = [40, 30, 15, 10, 5];
+pub static MAX_TIER: i32 = 5;
+
+/// Chance of a candidate having a trait, rolled once per slot
+static TRAIT_CHANCE: f64 = 0.35;
+static MAX_TRAITS: usize = 2;
+
+pub static MAX_MORALE: i32 = 100;
+static DEFAULT_MORALE: i32 = 70;
+/// Crew below this morale only pull half their weight
+static LOW_MORALE: i32 = 30;
+/// Morale gained for a jump with nothing going wrong
+static VOYAGE_MORALE: i32 = 2;
+/// Morale gained on being paid
+static PAYDAY_MORALE: i32 = 3;
+/// Morale lost every payday while a promotion they've earned is ignored
+static IMPATIENCE_MORALE: i32 = 5;
+/// Morale change on promotion with the full raise asked for, and with no raise at all. Offers in between land in between
+static PROMOTION_MORALE: i32 = 15;
+static NO_RAISE_MORALE: i32 = -15;
+static REFUSED_MORALE: i32 = -25;
+
+static EXPERIENCE_PER_TURN: i32 = 5;
+/// Experience needed to go up from a tier is this times the tier
+static EXPERIENCE_PER_TIER: i32 = 50;
+/// How much more a greedy crew member asks for on promotion
+static GREED: f32 = 1.5;
+/// Oldest history entries are dropped past this
+static MAX_HISTORY: usize = 20;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
 pub enum Job {
   Pilot,
   Mechanic,
@@ -10,11 +48,370 @@ pub enum Job {
   Misc
 }
 
-#[derive(Serialize, Deserialize)]
+/// Jobs candidates can be generated with
+static HIREABLE_JOBS: [Job; 5] = [Job::Pilot, Job::Mechanic, Job::Gunner, Job::Navigator, Job::Doctor];
+
+pub static ALL_JOBS: [Job; 6] = [Job::Pilot, Job::Mechanic, Job::Gunner, Job::Navigator, Job::Doctor, Job::Misc];
+
+impl Job {
+  /// Upper case name, as used in the .dat assets. Language keys are JOB_<KEY>
+  pub fn key(&self) -> &'static str {
+    match self {
+      Job::Pilot => "PILOT",
+      Job::Mechanic => "MECHANIC",
+      Job::Gunner => "GUNNER",
+      Job::Navigator => "NAVIGATOR",
+      Job::Doctor => "DOCTOR",
+      Job::Misc => "MISC",
+    }
+  }
+
+  pub fn from_key(key: &str) -> Option<Job> {
+    match key {
+      "PILOT" => Some(Job::Pilot),
+      "MECHANIC" => Some(Job::Mechanic),
+      "GUNNER" => Some(Job::Gunner),
+      "NAVIGATOR" => Some(Job::Navigator),
+      "DOCTOR" => Some(Job::Doctor),
+      "MISC" => Some(Job::Misc),
+      _ => None,
+    }
+  }
+
+  /// Specialists cost more than pilots and deckhands
+  fn salary_multiplier(&self) -> f32 {
+    match self {
+      Job::Pilot => 1.0,
+      Job::Mechanic => 1.1,
+      Job::Gunner => 1.2,
+      Job::Navigator => 1.1,
+      Job::Doctor => 1.4,
+      Job::Misc => 0.8,
+    }
+  }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum CrewTrait {
+  /// Loses morale half as fast
+  Loyal,
+  /// Asks for a lot more on promotion
+  Greedy,
+  /// Earns experience faster
+  QuickLearner,
+  /// Earns experience slower
+  Slacker,
+  /// Gains morale faster
+  Cheerful,
+}
+
+pub static ALL_TRAITS: [CrewTrait; 5] = [CrewTrait::Loyal, CrewTrait::Greedy, CrewTrait::QuickLearner, CrewTrait::Slacker, CrewTrait::Cheerful];
+
+impl CrewTrait {
+  /// Upper case name, language keys are TRAIT_<KEY>
+  pub fn key(&self) -> &'static str {
+    match self {
+      CrewTrait::Loyal => "LOYAL",
+      CrewTrait::Greedy => "GREEDY",
+      CrewTrait::QuickLearner => "QUICK_LEARNER",
+      CrewTrait::Slacker => "SLACKER",
+      CrewTrait::Cheerful => "CHEERFUL",
+    }
+  }
+
+  /// Whether someone with this trait can't also have the other
+  fn conflicts(&self, other: &CrewTrait) -> bool {
+    self == other || matches!(
+      (self, other),
+      (CrewTrait::QuickLearner, CrewTrait::Slacker) | (CrewTrait::Slacker, CrewTrait::QuickLearner)
+    )
+  }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HistoryEntry {
+  pub turn: i32,
+  pub message: Message,
+}
+
+fn default_morale() -> i32 {
+  DEFAULT_MORALE
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct CrewMember {
   pub name: String,
   pub job: Job,
   /// level 1 - 5, with tier comes salary but also effectiveness
   pub tier: i32,
+  /// Paid every payday, see GameState::advance_turn
   pub salary: i32,
-}
\ No newline at end of file
+  /// 0 - 100. Unhappy crew work worse, and walk out at 0
+  #[serde(default = "default_morale")]
+  pub morale: i32,
+  /// Earned flying, enough of it and they'll want a promotion
+  #[serde(default)]
+  pub experience: i32,
+  #[serde(default)]
+  pub traits: Vec<CrewTrait>,
+  /// What they've been through since being hired, oldest first
+  #[serde(default)]
+  pub history: Vec<HistoryEntry>,
+}
+
+impl CrewMember {
+  /// A candidate for the hiring hall. Higher tiers are rarer
+  pub fn generate(rng: &mut impl Rng) -> CrewMember {
+    let job = HIREABLE_JOBS[rng.gen_range(0..HIREABLE_JOBS.len())];
+
+    let mut roll = rng.gen_range(0..100);
+    let mut tier = 1;
+
+    for weight in TIER_WEIGHTS {
+      if roll < weight {
+        break;
+      }
+
+      roll -= weight;
+      tier += 1;
+    }
+
+    let salary = (SALARY_PER_TIER * tier) as f32 * job.salary_multiplier() * rng.gen_range(0.9..1.1);
+    let mut traits: Vec<CrewTrait> = Vec::new();
+
+    for _ in 0..MAX_TRAITS {
+      let candidate = ALL_TRAITS[rng.gen_range(0..ALL_TRAITS.len())];
+
+      if rng.gen_bool(TRAIT_CHANCE) && !traits.iter().any(|t| t.conflicts(&candidate)) {
+        traits.push(candidate);
+      }
+    }
+
+    CrewMember {
+      name: generate_name(rng, NameGenerationParams {
+        min: Some(2),
+        max: Some(4),
+        can_have_numeral: Some(false),
+        ..Default::default()
+      }),
+      job,
+      tier: tier.min(MAX_TIER),
+      salary: salary as i32,
+      morale: DEFAULT_MORALE,
+      experience: 0,
+      traits,
+      history: Vec::new(),
+    }
+  }
+
+  pub fn has_trait(&self, t: CrewTrait) -> bool {
+    self.traits.contains(&t)
+  }
+
+  /// Adds to their history, dropping the oldest entry once it's full
+  pub fn record(&mut self, turn: i32, message: Message) {
+    self.history.push(HistoryEntry { turn, message });
+
+    if self.history.len() > MAX_HISTORY {
+      self.history.remove(0);
+    }
+  }
+
+  pub fn change_morale(&mut self, change: i32) {
+    let change = match change {
+      change if change < 0 && self.has_trait(CrewTrait::Loyal) => change / 2,
+      change if change > 0 && self.has_trait(CrewTrait::Cheerful) => change * 3 / 2,
+      change => change,
+    };
+
+    self.morale = (self.morale + change).clamp(0, MAX_MORALE);
+  }
+
+  pub fn gain_experience(&mut self, amount: i32) {
+    let rate = if self.has_trait(CrewTrait::QuickLearner) {
+      1.5
+    } else if self.has_trait(CrewTrait::Slacker) {
+      0.5
+    } else {
+      1.
+    };
+
+    self.experience += (amount as f32 * rate) as i32;
+  }
+
+  /// Experience needed to go up from the current tier
+  pub fn promotion_threshold(&self) -> i32 {
+    EXPERIENCE_PER_TIER * self.tier
+  }
+
+  pub fn due_promotion(&self) -> bool {
+    self.tier < MAX_TIER && self.experience >= self.promotion_threshold()
+  }
+
+  /// What they'll ask to be paid at the next tier up
+  pub fn promotion_salary(&self) -> i32 {
+    let greed = if self.has_trait(CrewTrait::Greedy) { GREED } else { 1. };
+
+    (self.salary as f32 * (self.tier + 1) as f32 / self.tier as f32 * greed) as i32
+  }
+
+  /// Moves them up a tier on the offered salary, somewhere between what they're on now and what they asked for.
+  /// The further the offer falls short, the less happy they are about it
+  pub fn promote(&mut self, turn: i32, offer: i32) -> Result<(), GameError> {
+    if !self.due_promotion() {
+      return Err(GameError::NotDueForPromotion(self.name.clone()));
+    }
+
+    let demand = self.promotion_salary();
+    let offer = offer.clamp(self.salary, demand.max(self.salary));
+    let met = if demand > self.salary { (offer - self.salary) as f32 / (demand - self.salary) as f32 } else { 1. };
+
+    self.experience -= self.promotion_threshold();
+    self.tier += 1;
+    self.salary = offer;
+    self.change_morale(NO_RAISE_MORALE + ((PROMOTION_MORALE - NO_RAISE_MORALE) as f32 * met) as i32);
+    self.record(turn, Message::new("HISTORY_PROMOTED", &[("tier", self.tier.into()), ("salary", offer.into())]));
+
+    Ok(())
+  }
+
+  /// Turns down a promotion they've earned. They'll ask again once they've made up half the experience
+  pub fn refuse_promotion(&mut self, turn: i32) -> Result<(), GameError> {
+    if !self.due_promotion() {
+      return Err(GameError::NotDueForPromotion(self.name.clone()));
+    }
+
+    self.experience = self.promotion_threshold() / 2;
+    self.change_morale(REFUSED_MORALE);
+    self.record(turn, Message::new("HISTORY_PROMOTION_REFUSED", &[("tier", (self.tier + 1).into())]));
+
+    Ok(())
+  }
+
+  /// After a jump taking `turns`, and anything that happened on the way
+  pub fn voyage(&mut self, turn: i32, turns: i32, event: Option<&str>) {
+    self.gain_experience(EXPERIENCE_PER_TURN * turns);
+
+    match event {
+      Some(event) => self.record(turn, Message::new("HISTORY_EVENT", &[("event", Arg::Key(format!("EVENT_{}_TITLE", event)))])),
+      None => self.change_morale(VOYAGE_MORALE),
+    }
+  }
+
+  /// Morale changes for being paid. Anyone kept waiting on a promotion gets more impatient every payday
+  pub fn payday(&mut self) {
+    self.change_morale(PAYDAY_MORALE);
+
+    if self.due_promotion() {
+      self.change_morale(-IMPATIENCE_MORALE);
+    }
+  }
+
+  /// How much they add to their job's strength. Low morale halves it
+  fn strength(&self) -> f32 {
+    let morale = if self.morale < LOW_MORALE { 0.5 } else { 1. };
+
+    self.tier as f32 * STRENGTH_PER_TIER * morale
+  }
+}
+
+/// How good the crew is at a job, from 0 (nobody doing it) to 1. Every tier of every member in the job adds to it
+pub fn job_strength(crew: &[CrewMember], job: Job) -> f32 {
+  let strength: f32 = crew.iter().filter(|member| member.job == job).map(|member| member.strength()).sum();
+
+  strength.min(1.)
+}
+
+/// Unpaid crew member, for tests
+#[cfg(test)]
+pub fn test_member(job: Job, tier: i32) -> CrewMember {
+  CrewMember {
+    name: "Test".to_string(),
+    job,
+    tier,
+    salary: 0,
+    morale: DEFAULT_MORALE,
+    experience: 0,
+    traits: Vec::new(),
+    history: Vec::new(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::util::random::seeded_rng;
+
+  use super::{job_strength, test_member, CrewMember, CrewTrait, Job, MAX_TIER};
+
+  #[test]
+  fn strength_scales_with_tier_and_caps() {
+    let crew = vec![test_member(Job::Pilot, 1), test_member(Job::Navigator, 5)];
+
+    assert_eq!(job_strength(&crew, Job::Gunner), 0.);
+    assert!(job_strength(&crew, Job::Pilot) < job_strength(&crew, Job::Navigator));
+    assert_eq!(job_strength(&[test_member(Job::Pilot, 5), test_member(Job::Pilot, 5), test_member(Job::Pilot, 5)], Job::Pilot), 1.);
+  }
+
+  #[test]
+  fn candidates_are_in_range() {
+    let mut rng = seeded_rng("test");
+
+    for _ in 0..100 {
+      let candidate = CrewMember::generate(&mut rng);
+
+      assert!(candidate.tier >= 1 && candidate.tier <= MAX_TIER);
+      assert!(candidate.salary > 0);
+      assert!(!candidate.name.is_empty());
+      assert_ne!(candidate.job, Job::Misc);
+    }
+  }
+
+  #[test]
+  fn low_morale_weakens_the_crew() {
+    let mut crew = vec![test_member(Job::Pilot, 4)];
+    let happy = job_strength(&crew, Job::Pilot);
+
+    crew[0].change_morale(-100);
+
+    assert_eq!(crew[0].morale, 0);
+    assert!(job_strength(&crew, Job::Pilot) < happy);
+  }
+
+  #[test]
+  fn promotion_needs_experience_and_meets_demands() {
+    let mut pilot = test_member(Job::Pilot, 1);
+    pilot.salary = 100;
+
+    assert!(pilot.promote(0, 200).is_err());
+
+    pilot.voyage(10, 10, None);
+    assert!(pilot.due_promotion());
+
+    let demand = pilot.promotion_salary();
+    let morale = pilot.morale;
+    pilot.promote(10, demand * 2).unwrap();
+
+    // Overpaying doesn't get you anywhere
+    assert_eq!(pilot.salary, demand);
+    assert_eq!(pilot.tier, 2);
+    assert!(pilot.morale > morale);
+    assert_eq!(pilot.history.len(), 1);
+  }
+
+  #[test]
+  fn traits_change_how_crew_react() {
+    let mut loyal = test_member(Job::Gunner, 1);
+    let mut greedy = test_member(Job::Gunner, 1);
+
+    loyal.traits.push(CrewTrait::Loyal);
+    greedy.traits.push(CrewTrait::Greedy);
+    loyal.salary = 100;
+    greedy.salary = 100;
+
+    loyal.change_morale(-20);
+    greedy.change_morale(-20);
+
+    assert!(loyal.morale > greedy.morale);
+    assert!(greedy.promotion_salary() > loyal.promotion_salary());
+  }
+}
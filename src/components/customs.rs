@@ -0,0 +1,245 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::util::{error::GameError, language::{Arg, Message}};
+
+use super::{item::get_item_by_id, planet::SecurityLevel, player::Player, ship::Ship};
+
+/// Chance of being scanned on arrival at a Low, Medium and High security planet
+static SCAN_CHANCE: [f64; 3] = [0.05, 0.2, 0.45];
+/// Extra scan chance for every unit of contraband aboard, bigger loads are harder to hide
+static SCAN_CHANCE_PER_UNIT: f64 = 0.005;
+static MAX_SCAN_CHANCE: f64 = 0.95;
+
+/// Fine as a fraction of the contraband's value, before heat is taken into account
+static FINE_RATE: f32 = 0.5;
+/// Bribe asked for as a fraction of the contraband's value, per security level
+static BRIBE_RATE: [f32; 3] = [0.2, 0.35, 0.6];
+/// Chance an officer takes the bribe, per security level
+static BRIBE_CHANCE: [f64; 3] = [0.9, 0.6, 0.3];
+
+pub static MAX_HEAT: i32 = 100;
+/// Heat gained for being caught and paying the fine
+static HEAT_PER_CONVICTION: i32 = 25;
+/// Heat gained for a bribe that was taken. Someone still saw you
+static HEAT_PER_BRIBE: i32 = 5;
+/// Heat gained on top of the conviction for trying to bribe an honest officer
+static HEAT_PER_FAILED_BRIBE: i32 = 15;
+/// Heat lost every turn
+static HEAT_DECAY: i32 = 1;
+
+/// Contraband found in a customs scan, waiting on the player to pay up or try a bribe
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CustomsScan {
+  /// (item id, amount) of everything illegal in the hold
+  pub contraband: Vec<(u32, i32)>,
+  pub fine: i32,
+  pub bribe: i32,
+  /// Chance the bribe is accepted, 0 - 1
+  pub bribe_chance: f64,
+}
+
+/// Illegal goods in the hold, by item id
+pub fn contraband(ship: &Ship) -> Vec<(u32, i32)> {
+  let mut contraband: Vec<(u32, i32)> = ship.inventory.iter()
+    .filter(|(id, amount)| **amount > 0 && get_item_by_id(**id).is_some_and(|item| item.illegal))
+    .map(|(id, amount)| (*id as u32, *amount))
+    .collect();
+
+  contraband.sort();
+  contraband
+}
+
+/// Chance of a scan on arrival. Security sets the baseline, every unit of contraband and point of heat adds to it
+pub fn scan_chance(security: &SecurityLevel, units: i32, heat: i32) -> f64 {
+  let chance = SCAN_CHANCE[security.index()]
+    + units as f64 * SCAN_CHANCE_PER_UNIT
+    + heat.clamp(0, MAX_HEAT) as f64 / MAX_HEAT as f64 / 2.;
+
+  chance.min(MAX_SCAN_CHANCE)
+}
+
+/// Rolls for a customs scan on arrival. Nothing happens to a clean ship, or a lucky one
+pub fn scan(security: &SecurityLevel, player: &Player, rng: &mut impl Rng) -> Option<CustomsScan> {
+  let contraband = contraband(&player.ship);
+  let units: i32 = contraband.iter().map(|(_, amount)| amount).sum();
+
+  if units == 0 || !rng.gen_bool(scan_chance(security, units, player.heat)) {
+    return None;
+  }
+
+  // Officers value goods at the middle of their price band
+  let value: i32 = contraband.iter()
+    .filter_map(|(id, amount)| get_item_by_id(*id as i32).map(|item| (item.low_price + item.high_price) / 2 * amount))
+    .sum();
+
+  let heat = player.heat.clamp(0, MAX_HEAT) as f32 / MAX_HEAT as f32;
+  let index = security.index();
+
+  Some(CustomsScan {
+    contraband,
+    fine: (value as f32 * FINE_RATE * (1. + heat)) as i32,
+    bribe: (value as f32 * BRIBE_RATE[index]) as i32,
+    bribe_chance: (BRIBE_CHANCE[index] * (1. - heat as f64 / 2.)).max(0.),
+  })
+}
+
+/// Called once a turn
+pub fn cool_off(player: &mut Player) {
+  player.heat = (player.heat - HEAT_DECAY).max(0);
+}
+
+fn add_heat(player: &mut Player, heat: i32, changes: &mut Vec<Message>) {
+  let before = player.heat;
+  player.heat = (player.heat + heat).min(MAX_HEAT);
+  changes.push(Message::new("CHANGE_HEAT", &[("amount", (player.heat - before).into())]));
+}
+
+impl CustomsScan {
+  /// Contraband is confiscated and the fine paid, as far as the player's money goes
+  pub fn comply(&self, player: &mut Player) -> Vec<Message> {
+    let mut changes = Vec::new();
+
+    for (id, amount) in &self.contraband {
+      // Only what the scan found is seized, anything else of the same item stays in the hold
+      let held = player.ship.cargo_of(*id);
+      let seized = held.min(*amount);
+
+      if seized == held {
+        player.ship.inventory.remove(&(*id as i32));
+      } else {
+        player.ship.inventory.insert(*id as i32, held - seized);
+      }
+
+      changes.push(Message::new("CHANGE_CONFISCATED", &[("count", seized.into()), ("item", Arg::Item(*id))]));
+    }
+
+    let fine = self.fine.min(player.money);
+    player.money -= fine;
+    changes.push(Message::new("CHANGE_MONEY", &[("amount", (-fine).into())]));
+
+    add_heat(player, HEAT_PER_CONVICTION, &mut changes);
+
+    changes
+  }
+
+  /// Offers the bribe. The money is gone either way, if the officer refuses the player is also convicted
+  pub fn bribe(&self, player: &mut Player, rng: &mut impl Rng) -> Result<(bool, Vec<Message>), GameError> {
+    if player.money < self.bribe {
+      return Err(GameError::InsufficientFunds { needed: self.bribe, available: player.money });
+    }
+
+    player.money -= self.bribe;
+
+    let mut changes = vec![Message::new("CHANGE_MONEY", &[("amount", (-self.bribe).into())])];
+
+    if rng.gen_bool(self.bribe_chance.clamp(0., 1.)) {
+      add_heat(player, HEAT_PER_BRIBE, &mut changes);
+      return Ok((true, changes));
+    }
+
+    changes.extend(self.comply(player));
+    add_heat(player, HEAT_PER_FAILED_BRIBE, &mut changes);
+
+    Ok((false, changes))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{components::{item::get_item_by_name, planet::SecurityLevel, player::Player, ship::test_ship}, util::{error::GameError, language::{Arg, Message}, random::seeded_rng}};
+
+  use super::{scan_chance, CustomsScan, HEAT_PER_CONVICTION, MAX_HEAT};
+
+  fn test_player(money: i32) -> Player {
+    let mut player = Player::new("Test".to_string(), test_ship());
+
+    player.money = money;
+    player
+  }
+
+  fn test_scan(drugs: u32, bribe_chance: f64) -> CustomsScan {
+    CustomsScan {
+      contraband: vec![(drugs, 5)],
+      fine: 500,
+      bribe: 200,
+      bribe_chance,
+    }
+  }
+
+  #[test]
+  fn scans_scale_with_security_cargo_and_heat() {
+    assert!(scan_chance(&SecurityLevel::High, 1, 0) > scan_chance(&SecurityLevel::Low, 1, 0));
+    assert!(scan_chance(&SecurityLevel::Medium, 50, 0) > scan_chance(&SecurityLevel::Medium, 1, 0));
+    assert!(scan_chance(&SecurityLevel::Medium, 1, 80) > scan_chance(&SecurityLevel::Medium, 1, 0));
+    assert!(scan_chance(&SecurityLevel::High, 1000, MAX_HEAT) < 1.);
+  }
+
+  #[test]
+  fn complying_confiscates_and_fines() {
+    let drugs = get_item_by_name("DRUGS").unwrap().id;
+    let grain = get_item_by_name("GRAIN").unwrap().id;
+    let mut player = test_player(300);
+
+    player.ship.inventory.insert(drugs as i32, 5);
+    player.ship.inventory.insert(grain as i32, 5);
+    test_scan(drugs, 1.).comply(&mut player);
+
+    assert_eq!(player.ship.cargo_of(drugs), 0);
+    assert_eq!(player.ship.cargo_of(grain), 5);
+    // Fine is capped at what the player has
+    assert_eq!(player.money, 0);
+    assert_eq!(player.heat, HEAT_PER_CONVICTION);
+  }
+
+  #[test]
+  fn complying_only_seizes_what_was_found() {
+    let drugs = get_item_by_name("DRUGS").unwrap().id;
+    let mut player = test_player(1000);
+
+    player.ship.inventory.insert(drugs as i32, 8);
+
+    let changes = test_scan(drugs, 1.).comply(&mut player);
+
+    assert_eq!(player.ship.cargo_of(drugs), 3);
+    assert_eq!(changes[0], Message::new("CHANGE_CONFISCATED", &[("count", 5.into()), ("item", Arg::Item(drugs))]));
+  }
+
+  #[test]
+  fn accepted_bribe_keeps_the_goods() {
+    let drugs = get_item_by_name("DRUGS").unwrap().id;
+    let mut player = test_player(1000);
+
+    player.ship.inventory.insert(drugs as i32, 5);
+
+    let (taken, _) = test_scan(drugs, 1.).bribe(&mut player, &mut seeded_rng("test")).unwrap();
+
+    assert!(taken);
+    assert_eq!(player.money, 800);
+    assert_eq!(player.ship.cargo_of(drugs), 5);
+  }
+
+  #[test]
+  fn refused_bribe_costs_both() {
+    let drugs = get_item_by_name("DRUGS").unwrap().id;
+    let mut player = test_player(1000);
+
+    player.ship.inventory.insert(drugs as i32, 5);
+
+    let (taken, _) = test_scan(drugs, 0.).bribe(&mut player, &mut seeded_rng("test")).unwrap();
+
+    assert!(!taken);
+    assert_eq!(player.money, 300);
+    assert_eq!(player.ship.cargo_of(drugs), 0);
+    assert!(player.heat > HEAT_PER_CONVICTION);
+  }
+
+  #[test]
+  fn bribe_needs_the_money_up_front() {
+    let drugs = get_item_by_name("DRUGS").unwrap().id;
+    let mut player = test_player(100);
+
+    assert!(matches!(test_scan(drugs, 1.).bribe(&mut player, &mut seeded_rng("test")), Err(GameError::InsufficientFunds { .. })));
+    assert_eq!(player.money, 100);
+  }
+}
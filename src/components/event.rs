@@ -0,0 +1,403 @@
+use rand::Rng;
+use static_init::dynamic;
+
+use crate::{log, util::{dat, error::GameError, language::{Arg, Message}}};
+
+use super::{crew_member::{job_strength, Job}, item::{get_item_by_id, sorted_items}, planet::SecurityLevel, ship::Ship};
+
+/// Chance of anything happening at all on a jump
+static EVENT_CHANCE: f64 = 0.3;
+/// Share of an event's losses a crew at full strength in the event's mitigating job prevents
+static MITIGATION: f32 = 0.6;
+/// Experience for each crew member in the mitigating job, on top of the usual for the voyage
+static MITIGATION_EXPERIENCE: i32 = 10;
+
+#[dynamic]
+pub static EVENTS: Vec<TravelEvent> = get_all_events().unwrap_or_else(|e| {
+  log!("Failed to load events: {}", e);
+  Vec::new()
+});
+
+/// Either a flat change or a percentage of whatever is being changed
+#[derive(Debug, Clone, Copy)]
+pub enum Amount {
+  Flat(f32),
+  Percent(f32),
+}
+
+impl Amount {
+  fn parse(s: &str) -> Option<Amount> {
+    match s.strip_suffix('%') {
+      Some(percent) => percent.parse().ok().map(Amount::Percent),
+      None => s.parse().ok().map(Amount::Flat),
+    }
+  }
+
+  /// The change this amount makes to `value`
+  fn of(&self, value: f32) -> f32 {
+    match self {
+      Amount::Flat(amount) => *amount,
+      Amount::Percent(percent) => value * percent / 100.,
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+pub enum EventEffect {
+  Money(Amount),
+  Fuel(Amount),
+  /// Applied to every stack in the hold
+  Cargo(Amount),
+  /// A random item, between the two amounts, limited by free space
+  CargoRandom(i32, i32),
+  ConfiscateIllegal,
+  Crew(i32),
+  /// Applied to everyone aboard
+  Morale(i32),
+  Turns(i32),
+}
+
+#[derive(Debug, Clone)]
+pub struct TravelEvent {
+  pub name: String,
+  pub weight: f32,
+  /// Weight multiplier when flying from or to Low, Medium and High security planets
+  pub security: [f32; 3],
+  /// Weight multipliers that apply when a condition is met, see events.dat
+  pub modifiers: Vec<(String, f32)>,
+  pub effects: Vec<EventEffect>,
+  /// Crew in this job soften the event's losses
+  pub mitigation: Option<Job>,
+}
+
+/// What happened on a jump, ready to be shown to the player
+#[derive(Debug, Clone)]
+pub struct EventOutcome {
+  /// Event name, title and description come from EVENT_<NAME>_TITLE and EVENT_<NAME>_DESC
+  pub name: String,
+  pub changes: Vec<Message>,
+  pub extra_turns: i32,
+}
+
+impl TravelEvent {
+  /// How likely this event is on a jump between two planets with the given ship, relative to the others
+  pub fn weight_for(&self, origin: &SecurityLevel, destination: &SecurityLevel, ship: &Ship) -> f32 {
+    let security = (self.security[origin.index()] + self.security[destination.index()]) / 2.;
+    let mut weight = self.weight * security;
+
+    for (condition, multiplier) in &self.modifiers {
+      // Job conditions apply in part depending on the crew's tiers, and in full at full strength
+      let times = match condition.as_str() {
+        "CARGO" => (ship.cargo_used() > 0) as i32 as f32,
+        "ILLEGAL_CARGO" => ship.inventory.iter()
+          .any(|(id, amount)| *amount > 0 && get_item_by_id(*id).is_some_and(|item| item.illegal)) as i32 as f32,
+        "NO_CREW" => ship.crew.is_empty() as i32 as f32,
+        job => Job::from_key(job).map_or(0., |job| job_strength(&ship.crew, job)),
+      };
+
+      weight *= multiplier.powf(times);
+    }
+
+    weight
+  }
+
+  /// Applies every effect to the ship and player's money, describing each change
+  pub fn resolve(&self, ship: &mut Ship, money: &mut i32, rng: &mut impl Rng) -> EventOutcome {
+    let mut outcome = EventOutcome {
+      name: self.name.clone(),
+      changes: Vec::new(),
+      extra_turns: 0,
+    };
+
+    let mitigation = self.mitigation.map_or(0., |job| job_strength(&ship.crew, job) * MITIGATION);
+    // Only losses are softened, the crew can't make a windfall any bigger
+    let soften = |change: f32| if change < 0. { change * (1. - mitigation) } else { change };
+
+    if mitigation > 0. {
+      outcome.changes.push(Message::new("CHANGE_MITIGATED", &[("percent", (mitigation * 100.).into())]));
+
+      for member in ship.crew.iter_mut().filter(|member| Some(member.job) == self.mitigation) {
+        member.gain_experience(MITIGATION_EXPERIENCE);
+      }
+    }
+
+    for effect in &self.effects {
+      match effect {
+        EventEffect::Money(amount) => {
+          let change = (soften(amount.of(*money as f32)) as i32).max(-*money);
+          *money += change;
+          outcome.changes.push(Message::new("CHANGE_MONEY", &[("amount", change.into())]));
+        },
+        EventEffect::Fuel(amount) => {
+          let before = ship.fuel;
+          ship.fuel = (ship.fuel + soften(amount.of(ship.fuel))).clamp(0., ship.effective_spec().max_fuel);
+          outcome.changes.push(Message::new("CHANGE_FUEL", &[("amount", (ship.fuel - before).into())]));
+        },
+        EventEffect::Cargo(amount) => {
+          let mut lost = 0;
+
+          for held in ship.inventory.values_mut() {
+            let change = (soften(amount.of(*held as f32)) as i32).max(-*held);
+            *held += change;
+            lost -= change;
+          }
+
+          ship.inventory.retain(|_, held| *held > 0);
+
+          if lost > 0 {
+            outcome.changes.push(Message::new("CHANGE_CARGO_LOST", &[("count", lost.into())]));
+          }
+        },
+        EventEffect::CargoRandom(min, max) => {
+          let items = sorted_items();
+          let (id, _) = items[rng.gen_range(0..items.len())];
+          let amount = rng.gen_range(*min..=*max).min(ship.cargo_free());
+
+          if amount > 0 {
+            *ship.inventory.entry(*id as i32).or_insert(0) += amount;
+            outcome.changes.push(Message::new("CHANGE_CARGO_GAINED", &[("count", amount.into()), ("item", Arg::Item(*id))]));
+          }
+        },
+        EventEffect::ConfiscateIllegal => {
+          let mut confiscated = 0;
+
+          ship.inventory.retain(|id, held| {
+            let illegal = get_item_by_id(*id).is_some_and(|item| item.illegal);
+
+            if illegal {
+              confiscated += *held;
+            }
+
+            !illegal
+          });
+
+          if confiscated > 0 {
+            outcome.changes.push(Message::new("CHANGE_CONTRABAND_CONFISCATED", &[("count", confiscated.into())]));
+          }
+        },
+        EventEffect::Crew(change) => {
+          // Only losing crew is supported, nobody volunteers mid-flight
+          for _ in 0..(-change).max(0) {
+            if ship.crew.is_empty() {
+              break;
+            }
+
+            if rng.gen_bool(mitigation as f64) {
+              continue;
+            }
+
+            let member = ship.crew.remove(rng.gen_range(0..ship.crew.len()));
+            outcome.changes.push(Message::new("CHANGE_CREW_LEFT", &[("name", member.name.into())]));
+          }
+        },
+        EventEffect::Morale(change) => {
+          if ship.crew.is_empty() {
+            continue;
+          }
+
+          let change = soften(*change as f32) as i32;
+
+          for member in &mut ship.crew {
+            member.change_morale(change);
+          }
+
+          outcome.changes.push(Message::new("CHANGE_MORALE", &[("amount", change.into())]));
+        },
+        EventEffect::Turns(turns) => {
+          outcome.extra_turns += turns;
+          outcome.changes.push(Message::new("CHANGE_TURNS", &[("count", (*turns).into())]));
+        },
+      }
+    }
+
+    outcome
+  }
+}
+
+/// Rolls for an event on a jump. Most jumps are uneventful
+pub fn roll_event(origin: &SecurityLevel, destination: &SecurityLevel, ship: &Ship, rng: &mut impl Rng) -> Option<&'static TravelEvent> {
+  if !rng.gen_bool(EVENT_CHANCE) {
+    return None;
+  }
+
+  let weights: Vec<f32> = EVENTS.iter().map(|event| event.weight_for(origin, destination, ship)).collect();
+  let total: f32 = weights.iter().sum();
+
+  if total <= 0. {
+    return None;
+  }
+
+  let mut roll = rng.gen_range(0.0..total);
+
+  for (event, weight) in EVENTS.iter().zip(weights) {
+    if roll < weight {
+      return Some(event);
+    }
+
+    roll -= weight;
+  }
+
+  None
+}
+
+fn parse_effect(s: &str) -> Option<EventEffect> {
+  if s == "CONFISCATE_ILLEGAL" {
+    return Some(EventEffect::ConfiscateIllegal);
+  }
+
+  let (name, value) = s.split_once(':')?;
+
+  match name {
+    "MONEY" => Amount::parse(value).map(EventEffect::Money),
+    "FUEL" => Amount::parse(value).map(EventEffect::Fuel),
+    "CARGO" => Amount::parse(value).map(EventEffect::Cargo),
+    "CARGO_RANDOM" => {
+      let (min, max) = value.split_once("..")?;
+      Some(EventEffect::CargoRandom(min.parse().ok()?, max.parse().ok()?))
+    },
+    "CREW" => value.parse().ok().map(EventEffect::Crew),
+    "MORALE" => value.parse().ok().map(EventEffect::Morale),
+    "TURNS" => value.parse().ok().map(EventEffect::Turns),
+    _ => None,
+  }
+}
+
+pub fn get_all_events() -> Result<Vec<TravelEvent>, GameError> {
+  // Laid out as follows:
+  // NAME WEIGHT SECURITY_LOW,MEDIUM,HIGH MODIFIERS EFFECTS MITIGATION
+  dat::read("events.dat")?
+    .iter()
+    .map(|record| {
+      let name = record.text(0, "name")?.to_string();
+      let weight = record.parse(1, "weight")?;
+
+      let security = record.text(2, "security multipliers")?;
+      let security: Vec<f32> = security.split(',')
+        .map(|s| s.parse().map_err(|_| record.field_error(2, format!("Invalid security multipliers: {}", security))))
+        .collect::<Result<_, _>>()?;
+      let security: [f32; 3] = security.try_into()
+        .map_err(|_| record.field_error(2, "Expected three security multipliers".to_string()))?;
+
+      let modifiers = match record.get(3).unwrap_or("NONE") {
+        "NONE" => Vec::new(),
+        modifiers => modifiers.split(',')
+          .map(|modifier| {
+            modifier.split_once(':')
+              .and_then(|(condition, multiplier)| Some((condition.to_string(), multiplier.parse().ok()?)))
+              .ok_or_else(|| record.field_error(3, format!("Invalid modifier: {}", modifier)))
+          })
+          .collect::<Result<_, _>>()?,
+      };
+
+      let effects = record.get(4).unwrap_or("")
+        .split(',')
+        .filter(|effect| !effect.is_empty())
+        .map(|effect| parse_effect(effect).ok_or_else(|| record.field_error(4, format!("Invalid effect: {}", effect))))
+        .collect::<Result<_, _>>()?;
+
+      let mitigation = match record.get(5).unwrap_or("NONE") {
+        "NONE" => None,
+        job => Some(Job::from_key(job).ok_or_else(|| record.field_error(5, format!("Invalid mitigating job: {}", job)))?),
+      };
+
+      Ok(TravelEvent {
+        name,
+        weight,
+        security,
+        modifiers,
+        effects,
+        mitigation,
+      })
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{components::{crew_member::{test_member, Job}, item::get_item_by_name, planet::SecurityLevel, ship::{self, Ship}}, util::{language::Message, random::seeded_rng}};
+
+  use super::{get_all_events, EVENTS};
+
+  /// Room for a second hand, the crew tests take on a gunner
+  fn test_ship() -> Ship {
+    let mut ship = ship::test_ship();
+
+    ship.spec.max_crew = 2;
+    ship
+  }
+
+  fn event(name: &str) -> &'static super::TravelEvent {
+    EVENTS.iter().find(|event| event.name == name).unwrap()
+  }
+
+  #[test]
+  fn events_dat_parses() {
+    assert!(!get_all_events().unwrap().is_empty());
+  }
+
+  #[test]
+  fn gunners_scare_off_pirates() {
+    let pirates = event("PIRATE_AMBUSH");
+    let mut ship = test_ship();
+    let unguarded = pirates.weight_for(&SecurityLevel::Low, &SecurityLevel::Low, &ship);
+
+    ship.crew.push(test_member(Job::Gunner, 1));
+
+    assert!(pirates.weight_for(&SecurityLevel::Low, &SecurityLevel::Low, &ship) < unguarded);
+    assert!(pirates.weight_for(&SecurityLevel::High, &SecurityLevel::High, &ship) < pirates.weight_for(&SecurityLevel::Low, &SecurityLevel::Low, &ship));
+  }
+
+  #[test]
+  fn customs_only_confiscate_contraband() {
+    let drugs = get_item_by_name("DRUGS").unwrap().id as i32;
+    let grain = get_item_by_name("GRAIN").unwrap().id as i32;
+    let customs = event("CUSTOMS_INSPECTION");
+    let mut ship = test_ship();
+    let clean = customs.weight_for(&SecurityLevel::High, &SecurityLevel::High, &ship);
+
+    ship.inventory.insert(drugs, 5);
+    ship.inventory.insert(grain, 10);
+    assert!(customs.weight_for(&SecurityLevel::High, &SecurityLevel::High, &ship) > clean);
+
+    let mut money = 100;
+    let outcome = customs.resolve(&mut ship, &mut money, &mut seeded_rng("test"));
+
+    assert_eq!(ship.cargo_of(drugs as u32), 0);
+    assert_eq!(ship.cargo_of(grain as u32), 10);
+    assert_eq!(outcome.extra_turns, 1);
+  }
+
+  #[test]
+  fn percentage_losses_never_go_negative() {
+    let pirates = event("PIRATE_AMBUSH");
+    let mut ship = test_ship();
+    let mut money = 200;
+
+    pirates.resolve(&mut ship, &mut money, &mut seeded_rng("test"));
+    assert_eq!(money, 170);
+
+    let mut broke = 0;
+    pirates.resolve(&mut ship, &mut broke, &mut seeded_rng("test"));
+    assert_eq!(broke, 0);
+  }
+
+  #[test]
+  fn gunners_cut_pirate_losses() {
+    let pirates = event("PIRATE_AMBUSH");
+    let mut ship = test_ship();
+    let mut money = 1000;
+
+    pirates.resolve(&mut ship, &mut money, &mut seeded_rng("test"));
+    let unguarded = 1000 - money;
+
+    for _ in 0..2 {
+      ship.crew.push(test_member(Job::Gunner, 5));
+    }
+
+    money = 1000;
+    let outcome = pirates.resolve(&mut ship, &mut money, &mut seeded_rng("test"));
+
+    assert!(1000 - money < unguarded);
+    assert!(matches!(&outcome.changes[0], Message::Key { key, .. } if key == "CHANGE_MITIGATED"));
+  }
+}
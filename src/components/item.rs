@@ -1,13 +1,20 @@
-use std::{collections::HashMap, fs};
+use std::collections::{BTreeSet, HashMap};
 
 use serde::{Deserialize, Serialize};
 use static_init::dynamic;
 
-use crate::util::path::asset_path;
+use crate::{log, util::{dat::Record, error::GameError, mods}};
+
+use super::planet::get_all_poi_types;
 
 #[dynamic]
 pub static ITEMS: HashMap<u32, Item> = {
-  let items = get_all_items().unwrap();
+  // Checked again in main, where the error can be shown
+  let items = get_all_items().unwrap_or_else(|e| {
+    log!("Failed to load items: {}", e);
+    Vec::new()
+  });
+
   items.into_iter().map(|item| (item.id as u32, item)).collect()
 };
 
@@ -27,41 +34,129 @@ pub struct Item {
   pub illegal: bool,
 }
 
+impl Item {
+  /// Language key for the display name, `description` is already a key
+  pub fn name_key(&self) -> String {
+    format!("ITEM_{}", self.name)
+  }
+
+  pub fn category_key(&self) -> String {
+    format!("CATEGORY_{}", self.category)
+  }
+}
+
 pub fn get_item_by_id(id: i32) -> Option<Item> {
   ITEMS.get(&(id as u32)).cloned()
 }
 
-pub fn get_all_items() -> Result<Vec<Item>, std::io::Error> {
-  let asset_path = asset_path();
-  let items = fs::read_to_string(asset_path.join("items.dat"))?;
-
-  // Items.dat is a file with a list of items, one per line
-  // They are formatted as follows:
-  // NAME LOW HIGH ILLEGAL DESCRIPTION_KEY
-  // Skipping lines with a # in front of course
-  let items = items.lines()
-    .filter(|line| !line.starts_with('#'))
-    .map(|line| {
-      let mut parts = line.split_whitespace();
-      let id = parts.next().unwrap_or("0").parse().unwrap();
-      let name = parts.next().unwrap_or("Unknown").to_string();
-      let low_price = parts.next().unwrap_or("0").parse().unwrap();
-      let high_price = parts.next().unwrap_or("0").parse().unwrap();
-      let category = parts.next().unwrap_or("UNKNOWN_CATEGORY").to_string();
-      let illegal = parts.next().unwrap_or("false").to_lowercase().parse().unwrap();
-      let description = parts.next().unwrap_or("UNKNOWN_ITEM").to_string();
-
-      Item {
-        id: id,
-        name,
-        description,
-        low_price,
-        high_price,
-        category,
-        illegal,
-      }
-    })
+pub fn get_item_by_name(name: impl AsRef<str>) -> Option<Item> {
+  ITEMS.values().find(|item| item.name == name.as_ref()).cloned()
+}
+
+/// ITEMS is a HashMap, so its iteration order changes every run. Anything that needs a stable order
+/// (generators handing out seeded rolls, tables in the UI) should go through this instead
+pub fn sorted_items() -> Vec<(&'static u32, &'static Item)> {
+  let mut items: Vec<_> = ITEMS.iter().collect();
+  items.sort_by_key(|(id, _)| **id);
+  items
+}
+
+pub fn get_all_items() -> Result<Vec<Item>, GameError> {
+  let records = mods::read_layered("items.dat")?;
+
+  // Every category an item can be in has to be sold somewhere
+  let categories: BTreeSet<String> = get_all_poi_types()?.into_iter()
+    .flat_map(|poi_type| poi_type.categories)
     .collect();
 
+  let items = parse_items(&records, &categories)?;
+
+  let mut ids: Vec<u32> = items.iter().map(|item| item.id).collect();
+  ids.sort();
+
+  for pair in ids.windows(2).filter(|pair| pair[1] > pair[0] + 1) {
+    log!("items.dat skips ids {} to {}", pair[0] + 1, pair[1] - 1);
+  }
+
   Ok(items)
-}
\ No newline at end of file
+}
+
+/// Items.dat is a file with a list of items, one per line, formatted as follows:
+/// ID NAME LOW HIGH CATEGORY ILLEGAL DESCRIPTION_KEY
+fn parse_items(records: &[Record], categories: &BTreeSet<String>) -> Result<Vec<Item>, GameError> {
+  let mut parsed: Vec<Item> = Vec::new();
+
+  for record in records {
+    record.expect_len(7)?;
+
+    let id = record.parse(0, "id")?;
+    let low_price = record.parse(2, "low price")?;
+    let high_price = record.parse(3, "high price")?;
+    let category = record.text(4, "category")?;
+
+    if parsed.iter().any(|item| item.id == id) {
+      return Err(record.field_error(0, format!("Duplicate id: {}", id)));
+    }
+
+    if low_price < 0 || low_price > high_price {
+      return Err(record.field_error(2, format!("Low price {} must be between 0 and the high price {}", low_price, high_price)));
+    }
+
+    if !categories.contains(category) {
+      return Err(record.field_error(4, format!("Unknown category {}, no POI type in poi_types.dat sells it", category)));
+    }
+
+    parsed.push(Item {
+      id,
+      name: record.text(1, "name")?.to_string(),
+      description: record.text(6, "description")?.to_string(),
+      low_price,
+      high_price,
+      category: category.to_string(),
+      illegal: record.flag(5, "illegal flag")?,
+    });
+  }
+
+  Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::BTreeSet;
+
+  use crate::util::{dat, error::GameError};
+
+  use super::{get_all_items, parse_items};
+
+  #[test]
+  fn items_dat_parses() {
+    assert!(!get_all_items().unwrap().is_empty());
+  }
+
+  #[test]
+  fn descriptions_can_be_quoted() {
+    let categories = BTreeSet::from(["MINERALS".to_string()]);
+    let records = dat::parse("items.dat", "0 GOLD 10 20 MINERALS FALSE \"Shiny and heavy\" # comment").unwrap();
+
+    assert_eq!(parse_items(&records, &categories).unwrap()[0].description, "Shiny and heavy");
+  }
+
+  #[test]
+  fn bad_item_lines_report_where_they_went_wrong() {
+    let categories = BTreeSet::from(["MINERALS".to_string()]);
+    let err = |items: &str| match parse_items(&dat::parse("items.dat", items).unwrap(), &categories) {
+      Err(GameError::AssetParse { line, column, message, .. }) => (line, column, message),
+      other => panic!("expected a parse error, got {:?}", other.map(|items| items.len())),
+    };
+
+    assert_eq!(err("# header
+0 GOLD 10 20 MINERALS FALSE GOLD_DESC
+1 GEM ten 20 MINERALS FALSE GEM_DESC"), (3, Some(7), "Invalid low price: ten".to_string()));
+    assert_eq!(err("0 GOLD 10 20 MINERALS FALSE").0, 1);
+    assert!(err("0 GOLD 10 20 MINERALS FALSE GOLD_DESC
+0 GEM 10 20 MINERALS FALSE GEM_DESC").2.contains("Duplicate"));
+    assert!(err("0 GOLD 30 20 MINERALS FALSE GOLD_DESC").2.contains("high price"));
+    assert_eq!(err("0 GOLD 10 20 MINERAL FALSE GOLD_DESC").1, Some(14));
+    assert_eq!(err("0 GOLD 10 20 MINERALS MAYBE GOLD_DESC").1, Some(23));
+  }
+}
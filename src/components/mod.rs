@@ -1,8 +1,12 @@
+pub mod achievement;
 pub mod core;
 pub mod crew_member;
+pub mod customs;
+pub mod event;
 pub mod item;
 pub mod planet;
 pub mod player;
 pub mod progress;
 pub mod ship;
+pub mod shipyard;
 pub mod state;
\ No newline at end of file
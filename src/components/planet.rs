@@ -1,11 +1,11 @@
-use std::{collections::HashMap, fs};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 
-use crate::{log, util::{generators::{generate_name, NameGenerationParams}, path::asset_path, random::{pick_one, pick_x}}};
+use crate::{log, util::{error::GameError, generators::{generate_name, NameGenerationParams}, mods, random::{pick_one, pick_x}}};
 
-use super::{core::Core, item::{Item, ITEMS}};
+use super::{crew_member::CrewMember, item::{get_item_by_id, get_item_by_name, sorted_items, Item}, player::Player};
 
 static INV_MAX_MAIN_TYPE: i32 = 1000;
 static INV_MIN_MAIN_TYPE: i32 = 400;
@@ -22,6 +22,29 @@ static INV_MIN_ANY: i32 = 100;
 static DEM_MAX_ANY: i32 = 70;
 static DEM_MIN_ANY: i32 = 30;
 
+/// Units produced per turn when stock is at its usual level
+static PROD_MAIN_TYPE: i32 = 40;
+static PROD_SUB_TYPE: i32 = 24;
+static PROD_ANY: i32 = 12;
+
+/// Units of each item consumed per turn, for every order of magnitude of population past 10,000
+static CONSUMPTION_PER_MAGNITUDE: i32 = 2;
+/// How far demand moves towards its target each turn
+static DEMAND_REVERSION: f32 = 0.25;
+/// Demand points gained (or lost, when selling) by trading a POI's usual stock of an item in one go
+static TRADE_DEMAND_SHIFT: i32 = 20;
+
+/// Turns of market history kept per item, per POI
+static HISTORY_LENGTH: usize = 50;
+/// Price multiplier for illegal goods at POIs on Low security planets
+static BLACK_MARKET_PREMIUM: f32 = 1.5;
+/// Range of crew looking for work at each POI's hiring hall
+static MIN_CANDIDATES: usize = 2;
+static MAX_CANDIDATES: usize = 5;
+
+/// Price of a unit of fuel where fuel additives are in average demand
+static FUEL_BASE_PRICE: i32 = 4;
+
 #[derive(Serialize, Deserialize, Clone)]
 pub enum SecurityLevel {
   Low,
@@ -29,6 +52,17 @@ pub enum SecurityLevel {
   High,
 }
 
+impl SecurityLevel {
+  /// Position in per-level tables, eg. the security multipliers in events.dat
+  pub fn index(&self) -> usize {
+    match self {
+      SecurityLevel::Low => 0,
+      SecurityLevel::Medium => 1,
+      SecurityLevel::High => 2,
+    }
+  }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct Planet {
   pub name: String,
@@ -52,6 +86,33 @@ pub struct PointOfInterest {
   pub y: i32,
 
   pub types: (POIType, POIType),
+
+  /// Last HISTORY_LENGTH turns of market data per item, oldest first
+  #[serde(default)]
+  pub history: HashMap<u32, VecDeque<MarketSnapshot>>,
+
+  /// Illegal goods trade here at a premium. Only found on Low security planets
+  #[serde(default)]
+  pub black_market: bool,
+
+  /// Crew looking for work here
+  #[serde(default)]
+  pub candidates: Vec<CrewMember>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct MarketSnapshot {
+  pub turn: i32,
+  pub price: i32,
+  pub stock: i32,
+  pub demand: i32,
+}
+
+/// What a POI considers normal for an item, based on whether it's in the POI's main, sub or neither category
+struct MarketProfile {
+  stock: i32,
+  demand: i32,
+  production: i32,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -59,15 +120,23 @@ pub struct POIType {
   pub name: String,
   pub categories: Vec<String>,
   pub description: String,
+  /// Ships and upgrades are sold at POIs of this type
+  #[serde(default)]
+  pub shipyard: bool,
 }
 
-impl Planet {
-  pub fn generate() -> Planet {
-    let mut rng = rand::thread_rng();
+impl POIType {
+  /// Language key for the display name, `description` is already a key
+  pub fn name_key(&self) -> String {
+    format!("POI_TYPE_{}", self.name)
+  }
+}
 
+impl Planet {
+  pub fn generate(rng: &mut impl Rng) -> Planet {
     // Create 1-4 random POI
-    let poi: Vec<PointOfInterest> = (0..rng.gen_range(1..4))
-      .map(|_| PointOfInterest::generate())
+    let mut poi: Vec<PointOfInterest> = (0..rng.gen_range(1..4))
+      .map(|_| PointOfInterest::generate(rng))
       .collect();
 
     log!("Generated planet with {} POI", poi.len());
@@ -75,13 +144,38 @@ impl Planet {
     let x = rng.gen_range(0..100);
     let y = rng.gen_range(0..100);
 
+    let name = generate_name(rng, NameGenerationParams::default());
+    let population = rng.gen_range(100_000..10_000_000_000);
+    let security = pick_one(rng, vec![SecurityLevel::Low, SecurityLevel::Medium, SecurityLevel::High]);
+
+    for poi in &mut poi {
+      poi.black_market = matches!(security, SecurityLevel::Low);
+    }
+
     Planet {
-      name: generate_name(NameGenerationParams::default()),
-      population: rng.gen_range(100_000..10_000_000_000),
+      name,
+      population,
       poi,
       x,
       y,
-      security: pick_one(vec![SecurityLevel::Low, SecurityLevel::Medium, SecurityLevel::High]),
+      security,
+    }
+  }
+
+  /// Straight line distance on the system map
+  pub fn distance_to(&self, other: &Planet) -> f32 {
+    let dx = (self.x - other.x) as f32;
+    let dy = (self.y - other.y) as f32;
+
+    (dx * dx + dy * dy).sqrt()
+  }
+
+  /// Runs one turn of every market on the planet. Population is split evenly between POIs
+  pub fn tick_market(&mut self, rng: &mut impl Rng) {
+    let population = self.population / self.poi.len().max(1) as i64;
+
+    for poi in &mut self.poi {
+      poi.tick_market(population, rng);
     }
   }
 
@@ -102,13 +196,17 @@ impl Planet {
     avg_demand
   }
 
-  pub fn avg_prices(&self, core: &Core) -> HashMap<u32, i32> {
+  pub fn avg_prices(&self) -> HashMap<u32, i32> {
     let mut avg_prices = HashMap::new();
     for poi in &self.poi {
-      for (key, _) in &poi.inventory {
-        let item = core.items.iter().find(|item| item.id == *key).unwrap();
+      for key in poi.inventory.keys() {
+        let item = match get_item_by_id(*key as i32) {
+          Some(item) => item,
+          None => continue,
+        };
+
         let entry = avg_prices.entry(*key).or_insert(0);
-        *entry += poi.calculate_price(item.clone());
+        *entry += poi.calculate_price(item);
       }
     }
 
@@ -118,40 +216,64 @@ impl Planet {
 
     avg_prices
   }
+
+  /// Average price of an item across the planet's POIs for every recorded turn, oldest first
+  pub fn avg_price_history(&self, item_id: u32) -> Vec<(i32, i32)> {
+    // Turn -> (sum, count)
+    let mut by_turn: BTreeMap<i32, (i32, i32)> = BTreeMap::new();
+
+    for snapshot in self.poi.iter().filter_map(|poi| poi.history.get(&item_id)).flatten() {
+      let entry = by_turn.entry(snapshot.turn).or_insert((0, 0));
+      entry.0 += snapshot.price;
+      entry.1 += 1;
+    }
+
+    by_turn.into_iter()
+      .map(|(turn, (sum, count))| (turn, sum / count))
+      .collect()
+  }
+
+  pub fn record_history(&mut self, turn: i32) {
+    for poi in &mut self.poi {
+      poi.record_history(turn);
+    }
+  }
 }
 
 impl PointOfInterest {
-  pub fn generate() -> PointOfInterest {
-    let mut rng = rand::thread_rng();
+  pub fn generate(rng: &mut impl Rng) -> PointOfInterest {
     let x = rng.gen_range(0..100);
     let y = rng.gen_range(0..100);
-    let types = pick_x(get_all_poi_types().unwrap_or_else(|_| {
+    let types = pick_x(rng, get_all_poi_types().unwrap_or_else(|_| {
       log!("Failed to get POI types");
       Vec::new()
     }), false, 2);
 
     let mut poi = PointOfInterest {
-      name: generate_name(NameGenerationParams::default()),
+      name: generate_name(rng, NameGenerationParams::default()),
       description: "A point of interest".to_string(),
       inventory: HashMap::new(),
       demand: HashMap::new(),
       x,
       y,
       types: (types[0].clone(), types[1].clone()),
+      history: HashMap::new(),
+      black_market: false,
+      candidates: Vec::new(),
     };
 
-    poi.generate_inventory();
-    poi.generate_demand();
+    poi.generate_inventory(rng);
+    poi.generate_demand(rng);
+    poi.generate_candidates(rng);
 
     poi
   }
 
-  pub fn generate_inventory(&mut self) {
-    let mut rng = rand::thread_rng();
+  pub fn generate_inventory(&mut self, rng: &mut impl Rng) {
     let mut inventory = HashMap::new();
 
     // First iterate the items that fit the same category as the POI
-    ITEMS.iter()
+    sorted_items().into_iter()
       .for_each(|(id, item)| {
         // Main type, big amount
         if self.types.0.categories.contains(&item.category) {
@@ -169,12 +291,11 @@ impl PointOfInterest {
     self.inventory = inventory;
   }
 
-  pub fn generate_demand(&mut self) {
-    let mut rng = rand::thread_rng();
+  pub fn generate_demand(&mut self, rng: &mut impl Rng) {
     let mut demand = HashMap::new();
 
     // First iterate the items that fit the same category as the POI
-    ITEMS.iter()
+    sorted_items().into_iter()
       .for_each(|(id, item)| {
         log!("POI Category: {:?} | Item Category: {}", self.types.0.categories, item.category);
 
@@ -194,63 +315,225 @@ impl PointOfInterest {
     self.demand = demand;
   }
 
+  fn market_profile(&self, item: &Item) -> MarketProfile {
+    if self.types.0.categories.contains(&item.category) {
+      MarketProfile {
+        stock: (INV_MIN_MAIN_TYPE + INV_MAX_MAIN_TYPE) / 2,
+        demand: (DEM_MIN_MAIN_TYPE + DEM_MAX_MAIN_TYPE) / 2,
+        production: PROD_MAIN_TYPE,
+      }
+    } else if self.types.1.categories.contains(&item.category) {
+      MarketProfile {
+        stock: (INV_MIN_SUB_TYPE + INV_MAX_SUB_TYPE) / 2,
+        demand: (DEM_MIN_SUB_TYPE + DEM_MAX_SUB_TYPE) / 2,
+        production: PROD_SUB_TYPE,
+      }
+    } else {
+      MarketProfile {
+        stock: (INV_MIN_ANY + INV_MAX_ANY) / 2,
+        demand: (DEM_MIN_ANY + DEM_MAX_ANY) / 2,
+        production: PROD_ANY,
+      }
+    }
+  }
+
+  /// Runs one turn of the local economy. Production refills stock (slowing down as warehouses fill up),
+  /// the population eats into it, and demand drifts towards whatever the new stock level justifies
+  pub fn tick_market(&mut self, population: i64, rng: &mut impl Rng) {
+    // 1 for a small colony, up to 6 for the most crowded planets
+    let magnitude = ((population.max(1) as f64).log10() - 4.).max(1.) as i32;
+
+    for (id, item) in sorted_items() {
+      let profile = self.market_profile(item);
+      let stock = self.inventory.entry(*id).or_insert(0);
+
+      // Double production when empty, none at all at twice the usual stock
+      let production = (profile.production * (2 * profile.stock - *stock) / profile.stock).max(0)
+        + rng.gen_range(0..=profile.production / 4);
+      let consumption = magnitude * CONSUMPTION_PER_MAGNITUDE + rng.gen_range(0..=CONSUMPTION_PER_MAGNITUDE);
+
+      *stock = (*stock + production - consumption).clamp(0, 2 * profile.stock);
+
+      // Scarce goods are wanted more, a glut is wanted less
+      let target = (profile.demand * profile.stock / (*stock).max(profile.stock / 10)).clamp(1, 100);
+      let demand = self.demand.entry(*id).or_insert(profile.demand);
+      let shift = ((target - *demand) as f32 * DEMAND_REVERSION).round() as i32;
+
+      *demand = (*demand + shift).clamp(1, 100);
+    }
+  }
+
+  pub fn record_history(&mut self, turn: i32) {
+    for (id, item) in sorted_items() {
+      let snapshot = MarketSnapshot {
+        turn,
+        price: self.calculate_price(item.clone()),
+        stock: self.inventory.get(id).copied().unwrap_or(0),
+        demand: self.demand.get(id).copied().unwrap_or(0),
+      };
+
+      let history = self.history.entry(*id).or_default();
+      history.push_back(snapshot);
+
+      while history.len() > HISTORY_LENGTH {
+        history.pop_front();
+      }
+    }
+  }
+
+  /// Replaces whoever is in the hiring hall with a fresh set of candidates
+  pub fn generate_candidates(&mut self, rng: &mut impl Rng) {
+    let count = rng.gen_range(MIN_CANDIDATES..=MAX_CANDIDATES);
+    self.candidates = (0..count).map(|_| CrewMember::generate(rng)).collect();
+  }
+
+  /// Takes on a candidate from the hiring hall. Their first salary is paid up front as a signing fee
+  pub fn hire(&mut self, player: &mut Player, candidate: usize) -> Result<(), GameError> {
+    let member = match self.candidates.get(candidate) {
+      Some(member) => member,
+      None => return Err(GameError::InvalidAmount(candidate as i32)),
+    };
+
+    let crew = player.ship.crew.len() as i32;
+    let max = player.ship.effective_spec().max_crew;
+
+    if crew >= max {
+      return Err(GameError::CrewFull { crew: crew + 1, max });
+    }
+
+    if player.money < member.salary {
+      return Err(GameError::InsufficientFunds { needed: member.salary, available: player.money });
+    }
+
+    player.money -= member.salary;
+    player.ship.crew.push(self.candidates.remove(candidate));
+
+    Ok(())
+  }
+
+  pub fn has_shipyard(&self) -> bool {
+    self.types.0.shipyard || self.types.1.shipyard
+  }
+
+  /// Fuel is cheaper where fuel additives are plentiful (and so in low demand)
+  pub fn fuel_price(&self) -> i32 {
+    let demand = get_item_by_name("FUEL_ADDITIVES")
+      .and_then(|item| self.demand.get(&item.id).copied())
+      .unwrap_or(50);
+
+    (FUEL_BASE_PRICE * (50 + demand) / 100).max(1)
+  }
+
+  /// Fills the tank as far as the player's money allows, returning how much fuel was bought
+  pub fn refuel(&self, player: &mut Player) -> Result<f32, GameError> {
+    let price = self.fuel_price();
+    let missing = player.ship.effective_spec().max_fuel - player.ship.fuel;
+
+    if missing <= 0. {
+      return Err(GameError::TankFull);
+    }
+
+    // Partial units are rounded up when paying
+    let affordable = (player.money / price) as f32;
+    let amount = missing.min(affordable);
+
+    if amount <= 0. {
+      return Err(GameError::InsufficientFunds { needed: price, available: player.money });
+    }
+
+    player.ship.fuel += amount;
+    player.money -= (amount.ceil() as i32) * price;
+
+    Ok(amount)
+  }
+
+  /// Trades move demand straight away rather than waiting for the next tick
+  fn shift_demand(&mut self, item: &Item, amount: i32) {
+    let profile = self.market_profile(item);
+    let demand = self.demand.entry(item.id).or_insert(profile.demand);
+
+    *demand = (*demand + amount * TRADE_DEMAND_SHIFT / profile.stock).clamp(1, 100);
+  }
+
+  /// Demand maps linearly onto the item's price band, so prices can never leave it.
+  /// The one exception is contraband on the black market, which goes for a premium on top
   pub fn calculate_price(&self, item: Item) -> i32 {
-    let item_id = item.id;
-    let demand = self.demand.get(&item_id).unwrap_or(&0);
-    let avg_demand = self.demand.values().sum::<i32>() / self.demand.len() as i32;
-    let price = item.low_price + (item.high_price - item.low_price) * demand / avg_demand;
+    let demand = self.demand.get(&item.id).copied().unwrap_or(0).clamp(0, 100);
+    let price = item.low_price + (item.high_price - item.low_price) * demand / 100;
 
-    price
+    if item.illegal && self.black_market {
+      (price as f32 * BLACK_MARKET_PREMIUM) as i32
+    } else {
+      price
+    }
   }
 
-  /// Player buys items from POI
-  pub fn buy(&mut self, core: &mut Core, item_id: u32, amount: i32) -> Result<(), std::io::Error> {
-    if core.state.is_none() {
-      return Err(std::io::Error::new(std::io::ErrorKind::NotFound, "Player not found"));
+  /// Player buys items from POI, moving them into the ship's hold
+  pub fn buy(&mut self, player: &mut Player, item_id: u32, amount: i32) -> Result<(), GameError> {
+    if amount <= 0 {
+      return Err(GameError::InvalidAmount(amount));
     }
 
-    let state = core.state.as_mut().unwrap();
-    let player = &mut state.player;
-    let item = core.items.iter().find(|item| item.id == item_id).unwrap();
+    let item = match get_item_by_id(item_id as i32) {
+      Some(item) => item,
+      None => return Err(GameError::UnknownItem(item_id)),
+    };
 
     // Ensure POI has enough items
-    let entry = self.inventory.entry(item_id).or_insert(0);
-    if *entry < amount {
-      return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "Not enough to sell"));
+    let stock = self.inventory.get(&item_id).copied().unwrap_or(0);
+    if stock < amount {
+      return Err(GameError::InsufficientStock { item_id, requested: amount, available: stock });
+    }
+
+    let free = player.ship.cargo_free();
+    if free < amount {
+      return Err(GameError::CargoFull { requested: amount, free });
     }
 
     // Converting to integer is intentional
-    let price = self.calculate_price(item.clone()) as i32 * amount;
+    let price = self.calculate_price(item.clone()) * amount;
 
     if player.money < price {
-      return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "Not enough money"));
+      return Err(GameError::InsufficientFunds { needed: price, available: player.money });
     }
 
-    let entry = self.inventory.entry(item_id).or_insert(0);
-    *entry -= amount;
+    *self.inventory.entry(item_id).or_insert(0) -= amount;
+    *player.ship.inventory.entry(item_id as i32).or_insert(0) += amount;
+    self.shift_demand(&item, amount);
 
     player.money -= price;
 
     Ok(())
   }
 
-  pub fn sell(&mut self, core: &mut Core, item_id: u32, amount: i32) -> Result<(), std::io::Error> {
-    if core.state.is_none() {
-      return Err(std::io::Error::new(std::io::ErrorKind::NotFound, "Player not found"));
+  /// Player sells items to POI, taking them out of the ship's hold
+  pub fn sell(&mut self, player: &mut Player, item_id: u32, amount: i32) -> Result<(), GameError> {
+    if amount <= 0 {
+      return Err(GameError::InvalidAmount(amount));
     }
 
-    let state = core.state.as_mut().unwrap();
-    let player = &mut state.player;
-    let item = core.items.iter().find(|item| item.id == item_id).unwrap();
+    let item = match get_item_by_id(item_id as i32) {
+      Some(item) => item,
+      None => return Err(GameError::UnknownItem(item_id)),
+    };
+
+    let held = player.ship.cargo_of(item_id);
+    if held < amount {
+      return Err(GameError::NotHeld { item_id, requested: amount, held });
+    }
 
-    let price = self.calculate_price(item.clone()) as i32 * amount;
+    let price = self.calculate_price(item.clone()) * amount;
 
-    let entry = self.inventory.entry(item_id).or_insert(0);
-    if *entry < amount {
-      return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "Not enough items"));
+    let held = player.ship.inventory.entry(item_id as i32).or_insert(0);
+    *held -= amount;
+
+    // Don't leave empty entries lying around in the hold
+    if *held == 0 {
+      player.ship.inventory.remove(&(item_id as i32));
     }
 
-    *entry += amount;
+    *self.inventory.entry(item_id).or_insert(0) += amount;
+    self.shift_demand(&item, -amount);
 
     player.money += price;
 
@@ -258,27 +541,302 @@ impl PointOfInterest {
   }
 }
 
-pub fn get_all_poi_types() -> Result<Vec<POIType>, std::io::Error> {
-  let asset_path = asset_path();
-  let items = fs::read_to_string(asset_path.join("poi_types.dat"))?;
-
+pub fn get_all_poi_types() -> Result<Vec<POIType>, GameError> {
   // Laid out as follows:
-  // NAME CATEGORY1,CATEGORY2,... DESCRIPTION
-  let items = items.lines()
-    .filter(|line| !line.starts_with('#'))
-    .map(|line| {
-      let mut parts = line.split_whitespace();
-      let name = parts.next().unwrap_or("UNKNOWN").to_string();
-      let categories = parts.next().unwrap_or("UNKNOWN").split(',').map(|s| s.to_string()).collect();
-      let description = parts.next().unwrap_or("UNKNOWN").to_string();
-
-      POIType {
-        name,
-        description,
-        categories,
-      }
+  // NAME CATEGORY1,CATEGORY2,... DESCRIPTION SHIPYARD
+  mods::read_layered("poi_types.dat")?
+    .iter()
+    .map(|record| {
+      record.expect_len(4)?;
+
+      Ok(POIType {
+        name: record.text(0, "name")?.to_string(),
+        categories: record.text(1, "categories")?.split(',').map(|s| s.to_string()).collect(),
+        description: record.text(2, "description")?.to_string(),
+        shipyard: record.flag(3, "shipyard flag")?,
+      })
     })
-    .collect();
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashMap;
+
+  use crate::{components::{player::Player, ship::test_ship}, util::error::GameError};
+
+  use crate::util::random::seeded_rng;
+
+  use super::{sorted_items, POIType, Planet, PointOfInterest, SecurityLevel};
+
+  // AGRICULTURAL_PRODUCTS and ALGAE_BASED_FOODS from items.dat
+  static ITEM_A: u32 = 0;
+  static ITEM_B: u32 = 1;
 
-  Ok(items)
-}
\ No newline at end of file
+  fn test_poi(stock: i32) -> PointOfInterest {
+    let poi_type = POIType {
+      name: "TEST".to_string(),
+      categories: vec![],
+      description: "TEST_DESC".to_string(),
+      shipyard: false,
+    };
+
+    PointOfInterest {
+      name: "Test POI".to_string(),
+      description: String::new(),
+      inventory: HashMap::from([(ITEM_A, stock), (ITEM_B, stock)]),
+      demand: HashMap::from([(ITEM_A, 50), (ITEM_B, 50)]),
+      x: 0,
+      y: 0,
+      types: (poi_type.clone(), poi_type),
+      history: HashMap::new(),
+      black_market: false,
+      candidates: Vec::new(),
+    }
+  }
+
+  fn test_player(money: i32, max_inventory: i32) -> Player {
+    let mut player = Player::new("Test".to_string(), test_ship());
+
+    player.ship.spec.max_inventory = max_inventory;
+    player.money = money;
+    player
+  }
+
+  #[test]
+  fn buy_moves_goods_into_hold() {
+    let mut poi = test_poi(100);
+    let mut player = test_player(100_000, 50);
+    let price = poi.calculate_price(super::get_item_by_id(ITEM_A as i32).unwrap());
+
+    poi.buy(&mut player, ITEM_A, 10).unwrap();
+
+    assert_eq!(poi.inventory[&ITEM_A], 90);
+    assert_eq!(player.ship.cargo_of(ITEM_A), 10);
+    assert_eq!(player.money, 100_000 - price * 10);
+  }
+
+  #[test]
+  fn buy_rejects_when_hold_is_full() {
+    let mut poi = test_poi(100);
+    let mut player = test_player(100_000, 15);
+
+    poi.buy(&mut player, ITEM_A, 10).unwrap();
+    // Capacity is shared between every item in the hold
+    assert!(matches!(poi.buy(&mut player, ITEM_B, 6), Err(GameError::CargoFull { requested: 6, free: 5 })));
+    poi.buy(&mut player, ITEM_B, 5).unwrap();
+
+    assert_eq!(player.ship.cargo_used(), 15);
+    assert_eq!(poi.inventory[&ITEM_B], 95);
+  }
+
+  #[test]
+  fn buy_rejects_without_stock() {
+    let mut poi = test_poi(5);
+    let mut player = test_player(100_000, 50);
+
+    assert!(matches!(poi.buy(&mut player, ITEM_A, 6), Err(GameError::InsufficientStock { available: 5, .. })));
+    assert_eq!(player.ship.cargo_used(), 0);
+    assert_eq!(player.money, 100_000);
+  }
+
+  #[test]
+  fn buy_rejects_without_money() {
+    let mut poi = test_poi(100);
+    let mut player = test_player(0, 50);
+
+    assert!(matches!(poi.buy(&mut player, ITEM_A, 1), Err(GameError::InsufficientFunds { available: 0, .. })));
+    assert_eq!(poi.inventory[&ITEM_A], 100);
+    assert_eq!(player.ship.cargo_used(), 0);
+  }
+
+  #[test]
+  fn buy_and_sell_reject_non_positive_amounts() {
+    let mut poi = test_poi(100);
+    let mut player = test_player(100_000, 50);
+
+    assert!(matches!(poi.buy(&mut player, ITEM_A, 0), Err(GameError::InvalidAmount(0))));
+    assert!(matches!(poi.buy(&mut player, ITEM_A, -5), Err(GameError::InvalidAmount(-5))));
+    assert!(matches!(poi.sell(&mut player, ITEM_A, -5), Err(GameError::InvalidAmount(-5))));
+    assert_eq!(player.money, 100_000);
+  }
+
+  #[test]
+  fn sell_moves_goods_out_of_hold() {
+    let mut poi = test_poi(100);
+    let mut player = test_player(100_000, 50);
+
+    poi.buy(&mut player, ITEM_A, 10).unwrap();
+    let money = player.money;
+    let price = poi.calculate_price(super::get_item_by_id(ITEM_A as i32).unwrap());
+
+    poi.sell(&mut player, ITEM_A, 4).unwrap();
+    assert_eq!(player.ship.cargo_of(ITEM_A), 6);
+    assert_eq!(poi.inventory[&ITEM_A], 94);
+    assert_eq!(player.money, money + price * 4);
+
+    // Selling the rest removes the entry entirely
+    poi.sell(&mut player, ITEM_A, 6).unwrap();
+    assert!(!player.ship.inventory.contains_key(&(ITEM_A as i32)));
+  }
+
+  #[test]
+  fn sell_rejects_goods_not_held() {
+    let mut poi = test_poi(100);
+    let mut player = test_player(100_000, 50);
+
+    assert!(matches!(poi.sell(&mut player, ITEM_A, 1), Err(GameError::NotHeld { held: 0, .. })));
+
+    poi.buy(&mut player, ITEM_A, 3).unwrap();
+    assert!(matches!(poi.sell(&mut player, ITEM_A, 4), Err(GameError::NotHeld { requested: 4, held: 3, .. })));
+    assert_eq!(player.ship.cargo_of(ITEM_A), 3);
+  }
+
+  #[test]
+  fn price_stays_within_band() {
+    let mut poi = test_poi(100);
+    let item = super::get_item_by_id(ITEM_A as i32).unwrap();
+
+    for demand in [-20, 0, 1, 50, 100, 250] {
+      poi.demand.insert(ITEM_A, demand);
+      let price = poi.calculate_price(item.clone());
+
+      assert!(price >= item.low_price && price <= item.high_price, "demand {} gave price {}", demand, price);
+    }
+  }
+
+  #[test]
+  fn black_market_only_marks_up_contraband() {
+    let mut poi = test_poi(100);
+    let drugs = super::get_item_by_name("DRUGS").unwrap();
+    let legal = super::get_item_by_id(ITEM_A as i32).unwrap();
+
+    poi.demand.insert(drugs.id, 50);
+
+    let street = poi.calculate_price(drugs.clone());
+    let honest = poi.calculate_price(legal.clone());
+
+    poi.black_market = true;
+    assert!(poi.calculate_price(drugs) > street);
+    assert_eq!(poi.calculate_price(legal), honest);
+  }
+
+  #[test]
+  fn trades_shift_demand() {
+    let mut poi = test_poi(1000);
+    let mut player = test_player(1_000_000, 1000);
+
+    poi.buy(&mut player, ITEM_A, 500).unwrap();
+    assert!(poi.demand[&ITEM_A] > 50);
+
+    poi.sell(&mut player, ITEM_A, 500).unwrap();
+    assert_eq!(poi.demand[&ITEM_A], 50);
+  }
+
+  #[test]
+  fn tick_keeps_market_in_bounds() {
+    let mut poi = test_poi(100);
+    let mut rng = seeded_rng("market");
+
+    for _ in 0..500 {
+      poi.tick_market(10_000_000_000, &mut rng);
+    }
+
+    for (id, item) in sorted_items() {
+      let stock = poi.inventory[id];
+      let demand = poi.demand[id];
+      let price = poi.calculate_price(item.clone());
+
+      assert!(stock >= 0);
+      assert!((1..=100).contains(&demand));
+      assert!(price >= item.low_price && price <= item.high_price);
+    }
+  }
+
+  #[test]
+  fn tick_raises_demand_for_scarce_goods() {
+    let mut poi = test_poi(0);
+    let mut rng = seeded_rng("scarce");
+
+    poi.tick_market(1_000_000, &mut rng);
+
+    // Restocking starts straight away, and demand climbs while stock is short
+    assert!(poi.inventory[&ITEM_A] > 0);
+    assert!(poi.demand[&ITEM_A] > 50);
+  }
+
+  #[test]
+  fn history_is_bounded() {
+    let mut poi = test_poi(100);
+
+    for turn in 0..(super::HISTORY_LENGTH as i32 + 10) {
+      poi.record_history(turn);
+    }
+
+    let history = &poi.history[&ITEM_A];
+    assert_eq!(history.len(), super::HISTORY_LENGTH);
+    assert_eq!(history.front().unwrap().turn, 10);
+    assert_eq!(history.back().unwrap().turn, super::HISTORY_LENGTH as i32 + 9);
+  }
+
+  #[test]
+  fn planet_history_averages_pois() {
+    let mut cheap = test_poi(100);
+    let mut pricey = test_poi(100);
+    cheap.demand.insert(ITEM_A, 0);
+    pricey.demand.insert(ITEM_A, 100);
+
+    let mut planet = Planet {
+      name: "Test Planet".to_string(),
+      population: 1_000_000,
+      poi: vec![cheap, pricey],
+      x: 0,
+      y: 0,
+      security: SecurityLevel::Low,
+    };
+
+    planet.record_history(0);
+
+    let item = super::get_item_by_id(ITEM_A as i32).unwrap();
+    assert_eq!(planet.avg_price_history(ITEM_A), vec![(0, (item.low_price + item.high_price) / 2)]);
+    assert_eq!(planet.avg_prices()[&ITEM_A], (item.low_price + item.high_price) / 2);
+  }
+
+  #[test]
+  fn refuel_fills_tank_within_budget() {
+    let poi = test_poi(100);
+    let price = poi.fuel_price();
+
+    let mut player = test_player(100_000, 10);
+    player.ship.fuel = 40.;
+    assert_eq!(poi.refuel(&mut player).unwrap(), 60.);
+    assert_eq!(player.ship.fuel, 100.);
+    assert_eq!(player.money, 100_000 - 60 * price);
+    assert!(matches!(poi.refuel(&mut player), Err(GameError::TankFull)));
+
+    let mut player = test_player(price * 5, 10);
+    player.ship.fuel = 0.;
+    assert_eq!(poi.refuel(&mut player).unwrap(), 5.);
+    assert_eq!(player.money, 0);
+    assert!(matches!(poi.refuel(&mut player), Err(GameError::InsufficientFunds { .. })));
+  }
+
+  #[test]
+  fn hiring_takes_the_fee_and_needs_quarters() {
+    let mut poi = test_poi(100);
+    let mut player = test_player(1000, 50);
+
+    poi.generate_candidates(&mut seeded_rng("test"));
+    let candidates = poi.candidates.len();
+    let fee = poi.candidates[0].salary;
+
+    poi.hire(&mut player, 0).unwrap();
+
+    assert_eq!(player.money, 1000 - fee);
+    assert_eq!(player.ship.crew.len(), 1);
+    assert_eq!(poi.candidates.len(), candidates - 1);
+
+    // Test ship only has room for one
+    assert!(matches!(poi.hire(&mut player, 0), Err(GameError::CrewFull { .. })));
+  }
+}
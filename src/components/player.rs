@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::util::language::Message;
+
 use super::ship::Ship;
 
 #[derive(Serialize, Deserialize)]
@@ -7,6 +9,9 @@ pub struct Player {
   pub name: String,
   pub money: i32,
   pub ship: Ship,
+  /// How wanted the player is by customs, 0 - 100. Goes up when caught with contraband and cools off over time
+  #[serde(default)]
+  pub heat: i32,
 }
 
 impl Player {
@@ -15,6 +20,42 @@ impl Player {
       name,
       ship,
       money: 0,
+      heat: 0,
     }
   }
+
+  /// Pays everyone's salary, in the order they were hired. Anyone the money runs out before quits,
+  /// as does anyone whose morale has hit rock bottom
+  pub fn pay_crew(&mut self) -> Vec<Message> {
+    let mut notices = Vec::new();
+    let mut paid = 0;
+
+    self.ship.crew.retain_mut(|member| {
+      if self.money < member.salary {
+        notices.push(Message::new("NOTICE_UNPAID", &[("name", member.name.as_str().into())]));
+        return false;
+      }
+
+      self.money -= member.salary;
+      paid += member.salary;
+      member.payday();
+
+      if member.morale <= 0 {
+        notices.push(Message::new("NOTICE_QUIT", &[("name", member.name.as_str().into())]));
+        return false;
+      }
+
+      if member.due_promotion() {
+        notices.push(Message::new("NOTICE_PROMOTION", &[("name", member.name.as_str().into())]));
+      }
+
+      true
+    });
+
+    if paid > 0 {
+      notices.insert(0, Message::new("NOTICE_PAID", &[("amount", paid.into())]));
+    }
+
+    notices
+  }
 }
\ No newline at end of file
@@ -1,12 +1,50 @@
+use std::fs;
+
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize)]
+use crate::util::{error::GameError, path::progress_path};
+
+/// Everything carried between games, like achievements. Kept in its own file in the game folder, not in any save
+#[derive(Serialize, Deserialize, Default)]
 pub struct Progression {
   pub achievments: Vec<i32>,
+  /// Lifetime count of trades, over every game
+  #[serde(default)]
+  pub trades: i32,
+  /// Lifetime units of contraband sold
+  #[serde(default)]
+  pub smuggled: i32,
 }
 
 impl Progression {
   pub fn has_achievement(&self, achievement: i32) -> bool {
     self.achievments.contains(&achievement)
   }
-}
\ No newline at end of file
+
+  /// Returns whether the achievement is new
+  pub fn unlock(&mut self, achievement: i32) -> bool {
+    if self.has_achievement(achievement) {
+      return false;
+    }
+
+    self.achievments.push(achievement);
+    true
+  }
+}
+
+/// Reads progress from the game folder. A first run has none yet, which isn't an error
+pub fn read_progress() -> Result<Progression, GameError> {
+  let path = progress_path();
+
+  if !path.exists() {
+    return Ok(Progression::default());
+  }
+
+  Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+pub fn write_progress(progress: &Progression) -> Result<(), GameError> {
+  fs::write(progress_path(), serde_json::to_string(progress)?)?;
+
+  Ok(())
+}
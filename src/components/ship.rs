@@ -1,7 +1,30 @@
 use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
+use static_init::dynamic;
 
-use super::crew_member::CrewMember;
+use crate::{log, util::{dat::Record, error::GameError, mods}};
+
+use super::{crew_member::{job_strength, CrewMember, Job}, item::get_item_by_name, progress::Progression, shipyard::get_upgrade_by_id};
+
+/// Fuel burned per unit of distance on the system map
+static FUEL_PER_DISTANCE: f32 = 0.5;
+/// Fuel use multiplier while carrying fuel additives
+static ADDITIVE_EFFICIENCY: f32 = 0.75;
+/// Item that gets mixed into the tank to stretch it further
+static FUEL_ADDITIVE_ITEM: &str = "FUEL_ADDITIVES";
+/// Fuel saved by a navigator crew at full strength
+static NAVIGATOR_SAVING: f32 = 0.3;
+
+/// Every ship in ships.dat by id. A broken ships.dat leaves this empty, main checks it up front so that ends up on the error screen
+#[dynamic]
+pub static SHIPS: HashMap<u32, ShipSpecification> = {
+  let ships = get_all_ships().unwrap_or_else(|e| {
+    log!("Failed to load ships: {}", e);
+    Vec::new()
+  });
+
+  ships.into_iter().map(|ship| (ship.id, ship)).collect()
+};
 
 #[derive(Serialize, Deserialize)]
 pub struct Ship {
@@ -9,12 +32,283 @@ pub struct Ship {
   pub fuel: f32,
   pub inventory: HashMap<i32, i32>,
   pub crew: Vec<CrewMember>,
+  /// Upgrade id to how many are fitted
+  #[serde(default)]
+  pub upgrades: HashMap<u32, i32>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ShipSpecification {
+  #[serde(default)]
+  pub id: u32,
   pub name: String,
+  #[serde(default)]
+  pub price: i32,
   pub max_fuel: f32,
   pub max_inventory: i32,
   pub max_crew: i32,
-}
\ No newline at end of file
+  /// Distance covered per turn, as a multiple of an average ship's
+  #[serde(default = "default_multiplier")]
+  pub speed: f32,
+  /// Structure points, how much of a beating the ship can take
+  #[serde(default = "default_hull")]
+  pub hull: i32,
+  /// Distance covered per unit of fuel, as a multiple of an average ship's
+  #[serde(default = "default_multiplier")]
+  pub fuel_efficiency: f32,
+  /// Achievement needed before the ship can be picked at the start of a game
+  #[serde(default)]
+  pub unlock: Option<i32>,
+}
+
+// Saves from before ships.dat only have the basic spec, they get an average ship's stats
+fn default_multiplier() -> f32 {
+  1.
+}
+
+fn default_hull() -> i32 {
+  100
+}
+
+impl ShipSpecification {
+  /// Language key for the display name
+  pub fn name_key(&self) -> String {
+    format!("SHIP_{}", self.name)
+  }
+
+  /// Ships without an unlock are always available
+  pub fn unlocked(&self, progress: Option<&Progression>) -> bool {
+    match self.unlock {
+      Some(achievement) => progress.is_some_and(|progress| progress.has_achievement(achievement)),
+      None => true,
+    }
+  }
+}
+
+impl Ship {
+  /// Fresh off the lot, full tank and an empty hold
+  pub fn new(spec: ShipSpecification) -> Ship {
+    Ship {
+      fuel: spec.max_fuel,
+      spec,
+      inventory: HashMap::new(),
+      crew: vec![],
+      upgrades: HashMap::new(),
+    }
+  }
+
+  /// The ship's spec with every fitted upgrade added on. Use this over `spec` for anything gameplay related
+  pub fn effective_spec(&self) -> ShipSpecification {
+    let mut spec = self.spec.clone();
+
+    for (id, count) in &self.upgrades {
+      if let Some(upgrade) = get_upgrade_by_id(*id) {
+        upgrade.apply(&mut spec, *count);
+      }
+    }
+
+    spec
+  }
+
+  /// Total units of cargo currently in the hold
+  pub fn cargo_used(&self) -> i32 {
+    self.inventory.values().sum()
+  }
+
+  pub fn cargo_free(&self) -> i32 {
+    self.effective_spec().max_inventory - self.cargo_used()
+  }
+
+  pub fn cargo_of(&self, item_id: u32) -> i32 {
+    self.inventory.get(&(item_id as i32)).copied().unwrap_or(0)
+  }
+
+  /// Item id of fuel additives, if any are aboard
+  fn fuel_additive(&self) -> Option<u32> {
+    get_item_by_name(FUEL_ADDITIVE_ITEM)
+      .map(|item| item.id)
+      .filter(|id| self.cargo_of(*id) > 0)
+  }
+
+  /// Fuel a jump of `distance` would burn. Navigators and carrying fuel additives make every jump cheaper
+  pub fn fuel_needed(&self, distance: f32) -> f32 {
+    let navigation = 1. - job_strength(&self.crew, Job::Navigator) * NAVIGATOR_SAVING;
+    let fuel = distance * FUEL_PER_DISTANCE / self.spec.fuel_efficiency * navigation;
+
+    match self.fuel_additive() {
+      Some(_) => fuel * ADDITIVE_EFFICIENCY,
+      None => fuel,
+    }
+  }
+
+  /// Burns the fuel for a jump, along with one unit of fuel additives if there are any aboard
+  pub fn burn_fuel(&mut self, distance: f32) -> Result<(), GameError> {
+    let needed = self.fuel_needed(distance);
+
+    if needed > self.fuel {
+      return Err(GameError::InsufficientFuel { needed, available: self.fuel });
+    }
+
+    self.fuel -= needed;
+
+    if let Some(id) = self.fuel_additive() {
+      let held = self.inventory.entry(id as i32).or_insert(0);
+      *held -= 1;
+
+      if *held == 0 {
+        self.inventory.remove(&(id as i32));
+      }
+    }
+
+    Ok(())
+  }
+}
+
+/// Every ship in the catalogue, cheapest first
+pub fn sorted_ships() -> Vec<&'static ShipSpecification> {
+  let mut ships: Vec<_> = SHIPS.values().collect();
+  ships.sort_by_key(|ship| (ship.price, ship.id));
+  ships
+}
+
+pub fn get_all_ships() -> Result<Vec<ShipSpecification>, GameError> {
+  parse_ships(&mods::read_layered("ships.dat")?)
+}
+
+/// Format is documented at the top of ships.dat. Every field is required
+fn parse_ships(records: &[Record]) -> Result<Vec<ShipSpecification>, GameError> {
+  let mut parsed: Vec<ShipSpecification> = Vec::new();
+
+  for record in records {
+    record.expect_len(10)?;
+
+    let ship = ShipSpecification {
+      id: record.parse(0, "id")?,
+      name: record.text(1, "name")?.to_string(),
+      price: record.parse(2, "price")?,
+      max_fuel: record.parse(3, "max fuel")?,
+      max_inventory: record.parse(4, "max inventory")?,
+      max_crew: record.parse(5, "max crew")?,
+      speed: record.parse(6, "speed")?,
+      hull: record.parse(7, "hull")?,
+      fuel_efficiency: record.parse(8, "fuel efficiency")?,
+      unlock: match record.text(9, "unlock")? {
+        "NONE" => None,
+        _ => Some(record.parse(9, "unlock")?),
+      },
+    };
+
+    if parsed.iter().any(|other| other.id == ship.id) {
+      return Err(record.field_error(0, format!("Duplicate id: {}", ship.id)));
+    }
+
+    if ship.price < 0 || ship.max_crew < 0 {
+      return Err(record.error("Price and max crew can't be negative".to_string()));
+    }
+
+    if ship.max_fuel <= 0. || ship.max_inventory <= 0 || ship.speed <= 0. || ship.hull <= 0 || ship.fuel_efficiency <= 0. {
+      return Err(record.error("Max fuel, max inventory, speed, hull and fuel efficiency must be positive".to_string()));
+    }
+
+    parsed.push(ship);
+  }
+
+  Ok(parsed)
+}
+
+/// Plain ship for tests: a full 100 fuel tank, 100 cargo, one crew berth and average stats
+#[cfg(test)]
+pub fn test_ship() -> Ship {
+  Ship::new(ShipSpecification {
+    id: 0,
+    name: "Test Ship".to_string(),
+    price: 0,
+    max_fuel: 100.,
+    max_inventory: 100,
+    max_crew: 1,
+    speed: 1.,
+    hull: 100,
+    fuel_efficiency: 1.,
+    unlock: None,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{components::{item::get_item_by_name, progress::Progression}, util::{dat, error::GameError}};
+
+  use super::{get_all_ships, parse_ships, test_ship};
+
+  #[test]
+  fn burn_fuel_deducts_distance_cost() {
+    let mut ship = test_ship();
+    let needed = ship.fuel_needed(40.);
+
+    ship.burn_fuel(40.).unwrap();
+    assert_eq!(ship.fuel, 100. - needed);
+  }
+
+  #[test]
+  fn burn_fuel_refuses_when_short() {
+    let mut ship = test_ship();
+    ship.fuel = 1.;
+
+    assert!(matches!(ship.burn_fuel(40.), Err(GameError::InsufficientFuel { .. })));
+    assert_eq!(ship.fuel, 1.);
+  }
+
+  #[test]
+  fn fuel_additives_stretch_fuel_and_get_used_up() {
+    let additives = get_item_by_name("FUEL_ADDITIVES").unwrap().id;
+    let mut ship = test_ship();
+    let plain = ship.fuel_needed(40.);
+
+    ship.inventory.insert(additives as i32, 1);
+    assert!(ship.fuel_needed(40.) < plain);
+
+    ship.burn_fuel(40.).unwrap();
+    assert_eq!(ship.cargo_of(additives), 0);
+    assert_eq!(ship.fuel_needed(40.), plain);
+  }
+
+  #[test]
+  fn efficient_ships_burn_less() {
+    let mut ship = test_ship();
+    let average = ship.fuel_needed(40.);
+
+    ship.spec.fuel_efficiency = 2.;
+    assert_eq!(ship.fuel_needed(40.), average / 2.);
+  }
+
+  #[test]
+  fn ships_dat_parses() {
+    let ships = get_all_ships().unwrap();
+
+    assert!(!ships.is_empty());
+    assert!(ships.iter().any(|ship| ship.price == 0), "there should be a free starter ship");
+  }
+
+  #[test]
+  fn bad_ship_lines_report_their_line() {
+    let err = |ships: &str| match parse_ships(&dat::parse("ships.dat", ships).unwrap()) {
+      Err(GameError::AssetParse { line, message, .. }) => (line, message),
+      other => panic!("expected a parse error, got {:?}", other.map(|ships| ships.len())),
+    };
+
+    assert_eq!(err("# header\n0 A 0 80 60 2 1.0 80 1.0 NONE\n1 B 0 80 60 2 fast 80 1.0 NONE").0, 3);
+    assert_eq!(err("0 A 0 80 60 2 1.0 80 1.0").0, 1);
+    assert!(err("0 A 0 80 60 2 1.0 80 1.0 NONE\n0 B 0 80 60 2 1.0 80 1.0 NONE").1.contains("Duplicate"));
+    assert!(err("0 A 0 80 60 2 0 80 1.0 NONE").1.contains("positive"));
+  }
+
+  #[test]
+  fn ships_unlock_with_their_achievement() {
+    let ships = parse_ships(&dat::parse("ships.dat", "0 A 0 80 60 2 1.0 80 1.0 NONE\n1 B 0 80 60 2 1.0 80 1.0 3").unwrap()).unwrap();
+    let progress = Progression { achievments: vec![3], ..Default::default() };
+
+    assert!(ships[0].unlocked(None));
+    assert!(!ships[1].unlocked(None));
+    assert!(!ships[1].unlocked(Some(&Progression { achievments: vec![1], ..Default::default() })));
+    assert!(ships[1].unlocked(Some(&progress)));
+  }
+}
@@ -0,0 +1,236 @@
+use std::collections::HashMap;
+
+use static_init::dynamic;
+
+use crate::{log, util::{dat, error::GameError}};
+
+use super::{player::Player, ship::{Ship, ShipSpecification}};
+
+/// Fraction of what was paid for a ship and its upgrades that the shipyard gives back on a trade-in
+static TRADE_IN_RATE: f32 = 0.6;
+
+#[dynamic]
+pub static UPGRADES: HashMap<u32, Upgrade> = {
+  let upgrades = get_all_upgrades().unwrap_or_else(|e| {
+    log!("Failed to load upgrades: {}", e);
+    Vec::new()
+  });
+
+  upgrades.into_iter().map(|upgrade| (upgrade.id, upgrade)).collect()
+};
+
+/// Which maximum in the spec an upgrade raises
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UpgradeStat {
+  Fuel,
+  Inventory,
+  Crew,
+}
+
+#[derive(Debug, Clone)]
+pub struct Upgrade {
+  pub id: u32,
+  pub name: String,
+  pub price: i32,
+  pub stat: UpgradeStat,
+  /// Added to the stat for every one fitted
+  pub amount: f32,
+  /// Most of this upgrade a single ship can take
+  pub max: i32,
+}
+
+impl Upgrade {
+  /// Language key for the display name
+  pub fn name_key(&self) -> String {
+    format!("UPGRADE_{}", self.name)
+  }
+
+  /// Adds `count` of this upgrade onto a spec
+  pub fn apply(&self, spec: &mut ShipSpecification, count: i32) {
+    match self.stat {
+      UpgradeStat::Fuel => spec.max_fuel += self.amount * count as f32,
+      UpgradeStat::Inventory => spec.max_inventory += self.amount as i32 * count,
+      UpgradeStat::Crew => spec.max_crew += self.amount as i32 * count,
+    }
+  }
+}
+
+pub fn get_upgrade_by_id(id: u32) -> Option<Upgrade> {
+  UPGRADES.get(&id).cloned()
+}
+
+/// Every upgrade, in id order
+pub fn sorted_upgrades() -> Vec<&'static Upgrade> {
+  let mut upgrades: Vec<_> = UPGRADES.values().collect();
+  upgrades.sort_by_key(|upgrade| upgrade.id);
+  upgrades
+}
+
+/// What the shipyard pays for a ship, including whatever's been fitted to it
+pub fn trade_in_value(ship: &Ship) -> i32 {
+  let upgrades: i32 = ship.upgrades.iter()
+    .filter_map(|(id, count)| get_upgrade_by_id(*id).map(|upgrade| upgrade.price * count))
+    .sum();
+
+  ((ship.spec.price + upgrades) as f32 * TRADE_IN_RATE) as i32
+}
+
+/// Price of a ship once the current one has been traded in. Negative when the player comes out ahead
+pub fn ship_cost(player: &Player, spec: &ShipSpecification) -> i32 {
+  spec.price - trade_in_value(&player.ship)
+}
+
+/// Trades the player's ship in for a new one. Cargo, crew and fuel move across, upgrades stay with the old ship
+pub fn buy_ship(player: &mut Player, spec: &ShipSpecification) -> Result<(), GameError> {
+  let cost = ship_cost(player, spec);
+
+  if cost > player.money {
+    return Err(GameError::InsufficientFunds { needed: cost, available: player.money });
+  }
+
+  let cargo = player.ship.cargo_used();
+  if cargo > spec.max_inventory {
+    return Err(GameError::CargoFull { requested: cargo, free: spec.max_inventory });
+  }
+
+  let crew = player.ship.crew.len() as i32;
+  if crew > spec.max_crew {
+    return Err(GameError::CrewFull { crew, max: spec.max_crew });
+  }
+
+  let old = std::mem::replace(&mut player.ship, Ship::new(spec.clone()));
+
+  player.ship.fuel = old.fuel.min(spec.max_fuel);
+  player.ship.inventory = old.inventory;
+  player.ship.crew = old.crew;
+  player.money -= cost;
+
+  log!("Traded {} in for {}, paying {}", old.spec.name, spec.name, cost);
+
+  Ok(())
+}
+
+/// Fits one more of an upgrade to the player's ship
+pub fn buy_upgrade(player: &mut Player, upgrade: &Upgrade) -> Result<(), GameError> {
+  let fitted = player.ship.upgrades.get(&upgrade.id).copied().unwrap_or(0);
+
+  if fitted >= upgrade.max {
+    return Err(GameError::UpgradeLimit { upgrade: upgrade.name_key(), max: upgrade.max });
+  }
+
+  if upgrade.price > player.money {
+    return Err(GameError::InsufficientFunds { needed: upgrade.price, available: player.money });
+  }
+
+  player.money -= upgrade.price;
+  *player.ship.upgrades.entry(upgrade.id).or_insert(0) += 1;
+
+  Ok(())
+}
+
+pub fn get_all_upgrades() -> Result<Vec<Upgrade>, GameError> {
+  // Laid out as follows:
+  // ID NAME PRICE STAT AMOUNT MAX
+  dat::read("upgrades.dat")?
+    .iter()
+    .map(|record| {
+      let id = record.parse(0, "id")?;
+      let name = record.text(1, "name")?.to_string();
+      let price = record.parse(2, "price")?;
+      let stat = match record.text(3, "stat")? {
+        "MAX_FUEL" => UpgradeStat::Fuel,
+        "MAX_INVENTORY" => UpgradeStat::Inventory,
+        "MAX_CREW" => UpgradeStat::Crew,
+        stat => return Err(record.field_error(3, format!("Invalid stat: {}", stat))),
+      };
+      let amount = record.parse(4, "amount")?;
+      let max = record.parse(5, "max")?;
+
+      Ok(Upgrade {
+        id,
+        name,
+        price,
+        stat,
+        amount,
+        max,
+      })
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{components::{player::Player, ship::{test_ship, Ship, ShipSpecification}}, util::error::GameError};
+
+  use super::{buy_ship, buy_upgrade, get_all_upgrades, trade_in_value, Upgrade, UpgradeStat};
+
+  fn test_spec(price: i32, max_inventory: i32) -> ShipSpecification {
+    let mut spec = test_ship().spec;
+
+    spec.price = price;
+    spec.max_inventory = max_inventory;
+    spec
+  }
+
+  fn test_player(money: i32) -> Player {
+    let mut player = Player::new("Test".to_string(), Ship::new(test_spec(1000, 100)));
+
+    player.money = money;
+    player
+  }
+
+  fn cargo_pod() -> Upgrade {
+    Upgrade {
+      id: 0,
+      name: "CARGO_POD".to_string(),
+      price: 500,
+      stat: UpgradeStat::Inventory,
+      amount: 20.,
+      max: 2,
+    }
+  }
+
+  #[test]
+  fn upgrades_dat_parses() {
+    assert!(!get_all_upgrades().unwrap().is_empty());
+  }
+
+  #[test]
+  fn trade_in_pays_for_part_of_the_new_ship() {
+    let mut player = test_player(3000);
+    let trade_in = trade_in_value(&player.ship);
+
+    assert!(trade_in > 0 && trade_in < 1000);
+
+    player.ship.inventory.insert(0, 50);
+    buy_ship(&mut player, &test_spec(3000, 200)).unwrap();
+
+    assert_eq!(player.money, 3000 - (3000 - trade_in));
+    assert_eq!(player.ship.spec.max_inventory, 200);
+    assert_eq!(player.ship.cargo_of(0), 50);
+  }
+
+  #[test]
+  fn new_ship_has_to_fit_the_cargo() {
+    let mut player = test_player(10_000);
+
+    player.ship.inventory.insert(0, 80);
+
+    assert!(matches!(buy_ship(&mut player, &test_spec(0, 50)), Err(GameError::CargoFull { .. })));
+    assert_eq!(player.ship.spec.max_inventory, 100);
+  }
+
+  #[test]
+  fn upgrades_change_the_effective_spec() {
+    let mut player = test_player(10_000);
+    let pod = cargo_pod();
+
+    buy_upgrade(&mut player, &pod).unwrap();
+    buy_upgrade(&mut player, &pod).unwrap();
+
+    assert!(matches!(buy_upgrade(&mut player, &pod), Err(GameError::UpgradeLimit { .. })));
+    assert_eq!(player.money, 9_000);
+    assert_eq!(player.ship.spec.max_inventory, 100);
+    assert_eq!(player.ship.effective_spec().max_inventory, 140);
+  }
+}
@@ -1,10 +1,24 @@
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 
-use super::{planet::Planet, player::Player, ship::Ship};
+use crate::{log, util::{error::GameError, language::Message, random::seeded_rng}};
+
+use super::{achievement::GameEvent, crew_member::{job_strength, Job}, customs::{self, CustomsScan}, event::{roll_event, EventOutcome}, planet::Planet, player::Player, ship::Ship};
+
+/// Distance on the system map an average ship covers in one turn
+static DISTANCE_PER_TURN: f32 = 25.0;
+/// Extra distance per turn with a pilot crew at full strength
+static PILOT_SPEEDUP: f32 = 0.5;
+/// Crew are paid every this many turns
+static PAYDAY_INTERVAL: i32 = 5;
+/// Hiring halls get a new set of candidates every this many turns
+static CANDIDATE_REFRESH: i32 = 10;
 
 #[derive(Serialize, Deserialize)]
 pub struct GameState {
+  /// World seed, the same seed always generates the same system
+  #[serde(default)]
+  pub seed: String,
   pub planets: Vec<Planet>,
 
   /// Index of the current planet
@@ -14,18 +28,49 @@ pub struct GameState {
 
   pub player: Player,
   pub turn: i32,
+
+  /// Event from the last jump, waiting to be shown. Its effects have already been applied
+  #[serde(skip)]
+  pub pending_event: Option<EventOutcome>,
+  /// Customs scan on arrival that the player hasn't dealt with yet. Saved, so reloading doesn't get you out of it
+  #[serde(default)]
+  pub pending_scan: Option<CustomsScan>,
+  /// Things that happened since the last jump that the player should hear about, eg. payday
+  #[serde(skip)]
+  pub notices: Vec<Message>,
+  /// Planets landed on so far, not counting the starting one
+  #[serde(default)]
+  pub visited: Vec<i32>,
+  /// Waiting to be counted towards achievements, see achievement::evaluate
+  #[serde(skip)]
+  pub events: Vec<GameEvent>,
 }
 
 impl GameState {
-  pub fn new(name: String, ship: Ship) -> GameState {
-    let mut rng = rand::thread_rng();
+  pub fn new(name: String, ship: Ship, seed: String) -> GameState {
+    let mut rng = seeded_rng(&seed);
+
+    log!("Generating system with seed {}", seed);
+
+    let mut planets: Vec<Planet> = (0..rng.gen_range(6..8)).map(|_| Planet::generate(&mut rng)).collect();
+
+    // Starting prices are the first point on every chart
+    for planet in &mut planets {
+      planet.record_history(0);
+    }
 
     GameState {
-      planets: (0..rng.gen_range(6..8)).map(|_| Planet::generate()).collect(),
+      planets,
+      seed,
       current_planet: 0,
       current_poi: 0,
       player: Player::new(name, ship),
       turn: 0,
+      pending_event: None,
+      pending_scan: None,
+      notices: Vec::new(),
+      visited: Vec::new(),
+      events: Vec::new(),
     }
   }
 
@@ -33,14 +78,253 @@ impl GameState {
     &self.planets[self.current_planet as usize]
   }
 
-  pub fn fly_to_planet(&mut self, planet_id: i32) -> bool {
-    if planet_id < 0 || planet_id >= self.planets.len() as i32 {
-      return false;
-    }
+  /// Fuel and turns needed to reach a planet from the current one
+  pub fn travel_cost(&self, planet_id: i32) -> Result<(f32, i32), GameError> {
+    let destination = match self.planets.get(planet_id as usize) {
+      Some(planet) if planet_id >= 0 => planet,
+      _ => return Err(GameError::UnknownPlanet(planet_id)),
+    };
+
+    let distance = self.current_planet().distance_to(destination);
+    let ship = &self.player.ship;
+    let per_turn = DISTANCE_PER_TURN * ship.spec.speed * (1. + job_strength(&ship.crew, Job::Pilot) * PILOT_SPEEDUP);
+    let turns = ((distance / per_turn).ceil() as i32).max(1);
+
+    Ok((self.player.ship.fuel_needed(distance), turns))
+  }
 
-    // TODO perform random events and such here
+  pub fn fly_to_planet(&mut self, planet_id: i32) -> Result<(), GameError> {
+    let (_, turns) = self.travel_cost(planet_id)?;
+    let distance = self.current_planet().distance_to(&self.planets[planet_id as usize]);
+
+    self.player.ship.burn_fuel(distance)?;
+    self.notices.clear();
+
+    let mut rng = seeded_rng(format!("{}:{}:travel", self.seed, self.turn));
+    let origin = &self.current_planet().security;
+    let destination = &self.planets[planet_id as usize].security;
+
+    self.pending_event = roll_event(origin, destination, &self.player.ship, &mut rng)
+      .map(|event| {
+        log!("Travel event: {}", event.name);
+        event.resolve(&mut self.player.ship, &mut self.player.money, &mut rng)
+      });
+
+    let extra_turns = self.pending_event.as_ref().map_or(0, |event| event.extra_turns);
 
     self.current_planet = planet_id;
-    true
+    self.current_poi = 0;
+    self.events.push(GameEvent::Arrived(planet_id));
+
+    self.pending_scan = customs::scan(&self.current_planet().security, &self.player, &mut rng);
+
+    if let Some(scan) = &self.pending_scan {
+      log!("Customs scan found {} kinds of contraband", scan.contraband.len());
+    }
+
+    for _ in 0..(turns + extra_turns).max(1) {
+      self.advance_turn();
+    }
+
+    let event = self.pending_event.as_ref().map(|event| event.name.as_str());
+
+    for member in &mut self.player.ship.crew {
+      member.voyage(self.turn, turns + extra_turns, event);
+    }
+
+    Ok(())
+  }
+
+  /// Takes on a candidate from the current POI's hiring hall
+  pub fn hire_crew(&mut self, candidate: usize) -> Result<(), GameError> {
+    let planet = &mut self.planets[self.current_planet as usize];
+    let poi = &mut planet.poi[self.current_poi as usize];
+
+    poi.hire(&mut self.player, candidate)?;
+
+    if let Some(member) = self.player.ship.crew.last_mut() {
+      member.record(self.turn, Message::new("HISTORY_HIRED", &[("poi", poi.name.as_str().into()), ("planet", planet.name.as_str().into())]));
+    }
+
+    Ok(())
+  }
+
+  /// Promotes a crew member on the offered salary, see CrewMember::promote
+  pub fn promote_crew(&mut self, member: usize, offer: i32) -> Result<(), GameError> {
+    match self.player.ship.crew.get_mut(member) {
+      Some(crew_member) => crew_member.promote(self.turn, offer),
+      None => Err(GameError::InvalidAmount(member as i32)),
+    }
   }
-}
\ No newline at end of file
+
+  pub fn refuse_promotion(&mut self, member: usize) -> Result<(), GameError> {
+    match self.player.ship.crew.get_mut(member) {
+      Some(crew_member) => crew_member.refuse_promotion(self.turn),
+      None => Err(GameError::InvalidAmount(member as i32)),
+    }
+  }
+
+  /// Settles the pending customs scan, either by complying or offering the bribe.
+  /// Returns whether the bribe was taken along with everything that changed
+  pub fn resolve_scan(&mut self, bribe: bool) -> Result<(bool, Vec<Message>), GameError> {
+    let scan = match &self.pending_scan {
+      Some(scan) => scan,
+      None => return Ok((false, Vec::new())),
+    };
+
+    let result = if bribe {
+      // Seeded off the turn like everything else, reloading won't change the officer's mind
+      let mut rng = seeded_rng(format!("{}:{}:bribe", self.seed, self.turn));
+      scan.bribe(&mut self.player, &mut rng)?
+    } else {
+      (false, scan.comply(&mut self.player))
+    };
+
+    self.pending_scan = None;
+
+    Ok(result)
+  }
+
+  /// Moves the game forward a turn, letting every market in the system run.
+  /// Each turn gets its own RNG off the world seed, so a loaded save plays out the same way
+  pub fn advance_turn(&mut self) {
+    self.turn += 1;
+    customs::cool_off(&mut self.player);
+
+    let mut rng = seeded_rng(format!("{}:{}", self.seed, self.turn));
+
+    for planet in &mut self.planets {
+      planet.tick_market(&mut rng);
+      planet.record_history(self.turn);
+
+      if self.turn % CANDIDATE_REFRESH == 0 {
+        for poi in &mut planet.poi {
+          poi.generate_candidates(&mut rng);
+        }
+      }
+    }
+
+    if self.turn % PAYDAY_INTERVAL == 0 {
+      let notices = self.player.pay_crew();
+      self.notices.extend(notices);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{components::{crew_member::{test_member, Job}, customs::CustomsScan, ship::test_ship}, util::{error::GameError, language::Message}};
+
+  use super::GameState;
+
+  fn test_state(fuel: f32) -> GameState {
+    let mut ship = test_ship();
+    ship.fuel = fuel;
+
+    GameState::new("Test".to_string(), ship, "TEST_SEED".to_string())
+  }
+
+  /// Planet furthest from the start, so the trip always costs something
+  fn furthest_planet(state: &GameState) -> i32 {
+    (1..state.planets.len() as i32)
+      .max_by(|a, b| {
+        let a = state.current_planet().distance_to(&state.planets[*a as usize]);
+        let b = state.current_planet().distance_to(&state.planets[*b as usize]);
+        a.total_cmp(&b)
+      })
+      .unwrap()
+  }
+
+  #[test]
+  fn same_seed_same_system() {
+    let a = test_state(100.);
+    let b = test_state(100.);
+
+    // Compared as Values, since HashMaps serialize in a different order every time
+    assert_eq!(serde_json::to_value(&a.planets).unwrap(), serde_json::to_value(&b.planets).unwrap());
+  }
+
+  #[test]
+  fn flying_costs_fuel_and_turns() {
+    let mut state = test_state(100.);
+    let target = furthest_planet(&state);
+    let (fuel, turns) = state.travel_cost(target).unwrap();
+
+    state.fly_to_planet(target).unwrap();
+
+    assert_eq!(state.current_planet, target);
+    assert_eq!(state.turn, turns);
+    assert_eq!(state.player.ship.fuel, 100. - fuel);
+  }
+
+  #[test]
+  fn flying_without_fuel_is_refused() {
+    let mut state = test_state(0.);
+    let target = furthest_planet(&state);
+
+    assert!(matches!(state.fly_to_planet(target), Err(GameError::InsufficientFuel { .. })));
+    assert_eq!(state.current_planet, 0);
+    assert_eq!(state.turn, 0);
+  }
+
+  #[test]
+  fn flying_nowhere_is_refused() {
+    let mut state = test_state(100.);
+
+    assert!(matches!(state.fly_to_planet(-1), Err(GameError::UnknownPlanet(-1))));
+    assert!(matches!(state.fly_to_planet(100), Err(GameError::UnknownPlanet(100))));
+  }
+
+  #[test]
+  fn settling_customs_clears_the_scan() {
+    let mut state = test_state(100.);
+
+    state.player.money = 1000;
+    state.player.ship.inventory.insert(7, 5);
+    state.pending_scan = Some(CustomsScan {
+      contraband: vec![(7, 5)],
+      fine: 100,
+      bribe: 50,
+      bribe_chance: 1.,
+    });
+
+    state.resolve_scan(false).unwrap();
+
+    assert!(state.pending_scan.is_none());
+    assert_eq!(state.player.money, 900);
+    assert_eq!(state.player.ship.cargo_of(7), 0);
+
+    // Heat cools off as turns go by
+    let heat = state.player.heat;
+    state.advance_turn();
+    assert!(state.player.heat < heat);
+  }
+
+  #[test]
+  fn unpaid_crew_quit_on_payday() {
+    let mut state = test_state(100.);
+
+    state.player.ship.spec.max_crew = 2;
+    state.player.money = 150;
+
+    for name in ["Paid", "Unpaid"] {
+      let mut member = test_member(Job::Pilot, 1);
+
+      member.name = name.to_string();
+      member.salary = 100;
+      state.player.ship.crew.push(member);
+    }
+
+    while state.turn % super::PAYDAY_INTERVAL != super::PAYDAY_INTERVAL - 1 {
+      state.advance_turn();
+    }
+
+    assert_eq!(state.player.ship.crew.len(), 2);
+    state.advance_turn();
+
+    assert_eq!(state.player.money, 50);
+    assert_eq!(state.player.ship.crew.len(), 1);
+    assert_eq!(state.player.ship.crew[0].name, "Paid");
+    assert!(state.notices.contains(&Message::new("NOTICE_UNPAID", &[("name", "Unpaid".into())])));
+  }
+}
@@ -1,19 +1,35 @@
-use components::{core::Core, item::ITEMS};
+use chrono::Local;
+use components::{core::Core, item::get_all_items, progress::read_progress, ship::get_all_ships};
 use macroquad::prelude::*;
 use states::Stage;
-use util::{config::{self, write_config}, generators::{generate_name, NameGenerationParams}, language, logger};
+use util::{config::{self, write_config}, generators::{generate_name, NameGenerationParams}, language, logger, mods};
+
+/// Window size and mode come from the config, the rest of it is applied once the window is up
+fn window_conf() -> Conf {
+  let config = config::read_config();
+
+  Conf {
+    window_title: "Untitled Space Economy".to_string(),
+    window_width: config.window_width,
+    window_height: config.window_height,
+    fullscreen: config.fullscreen,
+    ..Default::default()
+  }
+}
 
 mod components;
 mod states;
 mod util;
 
-#[macroquad::main("Untitled Space Economy")]
+#[macroquad::main(window_conf)]
 async fn main() {
   logger::init(true);
 
   let mut core = Core::new();
   let mut config = config::read_config();
 
+  states::options::apply(&config);
+
   log!("Core initialized");
 
   if config.language.is_empty() {
@@ -26,48 +42,78 @@ async fn main() {
   log!("Loading language file: {}", config.language);
 
   core.language_file = config.language.clone();
+  core.config = config;
   
-  let loaded_language = language::try_load_language(&mut core);
-  if loaded_language.is_err() {
-    log!("Failed to load language file: {}", core.language_file);
+  // Missing text falls back to English and then the raw key, so a bad language file doesn't stop the game
+  match language::try_load_language(&mut core) {
+    Ok(()) => log!("Language file loaded: {}", core.language_file),
+    Err(e) => log!("Failed to load language file {}: {}", core.language_file, e),
+  }
 
-    // TODO do something about this
-    // right now it doesn't matter, there isn't any text being used anywhere
+  language::check_languages();
+
+  let active_mods: Vec<String> = mods::active().into_iter().map(|active| active.id).collect();
+  if !active_mods.is_empty() {
+    log!("Active mods, in load order: {}", active_mods.join(", "));
   }
 
-  log!("Language file loaded: {}", core.language_file);
-  log!("Read {} items", ITEMS.len());
+  // ITEMS falls back to nothing at all, so check items.dat here where the error can be shown
+  match get_all_items() {
+    Ok(items) => log!("Read {} items", items.len()),
+    Err(e) => {
+      log!("Failed to load items: {}", e);
+      core.errors.push(format!("[{}] {}", Local::now().format("%H:%M:%S"), e));
+      core.current_stage = Stage::Error;
+    },
+  }
+
+  // SHIPS falls back to an empty catalogue, so check ships.dat here where the error can be shown
+  match get_all_ships() {
+    Ok(ships) => log!("Read {} ships", ships.len()),
+    Err(e) => {
+      log!("Failed to load ships: {}", e);
+      core.errors.push(format!("[{}] {}", Local::now().format("%H:%M:%S"), e));
+      core.current_stage = Stage::Error;
+    },
+  }
+
+  // Losing track of achievements isn't worth stopping the game over, start from nothing instead
+  core.progress = Some(read_progress().unwrap_or_else(|e| {
+    log!("Failed to load progress: {}", e);
+    Default::default()
+  }));
 
   loop {
     clear_background(BLACK);
 
-    log!("Current stage: {:?}", core.current_stage);
+    log_verbose!("Current stage: {:?}", core.current_stage);
 
     // Each state handles next_frame() itself
     let result = match core.current_stage {
       Stage::MainMenu => states::main_menu::draw(&mut core).await,
+      Stage::SaveSelect => states::save_select::draw(&mut core).await,
       Stage::ShipSelect => states::ship_select::draw(&mut core).await,
       Stage::SystemView => states::game::draw_system(&mut core).await,
       Stage::PlanetView => states::game::draw_planet(&mut core).await,
       Stage::POIView => states::game::draw_poi(&mut core).await,
-
-      Stage::Error => {
-        // We handle this elsewhere
-        Ok(())
-      },
+      Stage::Event => states::event::draw(&mut core).await,
+      Stage::Customs => states::customs::draw(&mut core).await,
+      Stage::Roster => states::roster::draw(&mut core).await,
+      Stage::Options => states::options::draw(&mut core).await,
+      Stage::Mods => states::mods::draw(&mut core).await,
+      Stage::Error => states::error::draw(&mut core).await,
     };
 
-    if result.is_err() {
-      let err = result.err().unwrap();
+    if let Err(err) = result {
       log!("Error in main loop: {:?}", err);
-      core.errors.push(
-        format!("{:?}: {}", err.kind(), err.to_string())
-      );
-      core.current_stage = Stage::Error;
-    }
-
-    if core.current_stage == Stage::Error {
-      states::error::draw(&core).await.unwrap();
+      core.errors.push(format!("[{}] {}", Local::now().format("%H:%M:%S"), err));
+
+      // Recoverable errors just drop back into the stage that raised them, the player gets told with a toast
+      if err.is_recoverable() {
+        states::toast::push(language::get(&core, "ERROR_TOAST"), language::message(&core, &err.message()));
+      } else {
+        core.current_stage = Stage::Error;
+      }
     }
   }
 }
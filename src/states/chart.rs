@@ -0,0 +1,157 @@
+use macroquad::prelude::*;
+
+use crate::{components::{core::Core, item::Item}, util::language};
+
+static FONT_SIZE: f32 = 14.0;
+static PADDING: f32 = 6.0;
+
+/// Colours handed out to planets in the system comparison, in order
+static PALETTE: &[Color] = &[RED, ORANGE, YELLOW, GREEN, SKYBLUE, PURPLE, PINK, BEIGE];
+
+pub struct Series {
+  pub label: String,
+  pub color: Color,
+  /// (turn, value) pairs, oldest first
+  pub points: Vec<(i32, i32)>,
+}
+
+/// Draws every series on shared axes. The value axis always includes 0 so lines aren't exaggerated
+pub fn draw_line_chart(core: &Core, area: Rect, title: &str, series: &[Series]) {
+  draw_rectangle(area.x, area.y, area.w, area.h, Color::new(0.08, 0.08, 0.1, 1.0));
+  draw_rectangle_lines(area.x, area.y, area.w, area.h, 1.0, DARKGRAY);
+  draw_text(title, area.x + PADDING, area.y + FONT_SIZE, FONT_SIZE, WHITE);
+
+  let points = series.iter().flat_map(|series| series.points.iter());
+  let (min_turn, max_turn, max_value) = points.fold((i32::MAX, i32::MIN, 0), |(min_turn, max_turn, max_value), (turn, value)| {
+    (min_turn.min(*turn), max_turn.max(*turn), max_value.max(*value))
+  });
+
+  if min_turn > max_turn {
+    draw_text(&language::get(core, "CHART_NO_DATA"), area.x + PADDING, area.y + FONT_SIZE * 2.5, FONT_SIZE, GRAY);
+    return;
+  }
+
+  // Leave room on the left for the value labels and below for the turn labels
+  let plot = Rect::new(
+    area.x + 50.,
+    area.y + FONT_SIZE * 1.5,
+    area.w - 50. - PADDING,
+    area.h - FONT_SIZE * 3.,
+  );
+
+  let turn_span = (max_turn - min_turn).max(1) as f32;
+  let max_value = max_value.max(1) as f32;
+  let to_screen = |turn: i32, value: i32| {
+    vec2(
+      plot.x + (turn - min_turn) as f32 / turn_span * plot.w,
+      plot.y + plot.h - value as f32 / max_value * plot.h,
+    )
+  };
+
+  draw_line(plot.x, plot.y, plot.x, plot.y + plot.h, 1.0, GRAY);
+  draw_line(plot.x, plot.y + plot.h, plot.x + plot.w, plot.y + plot.h, 1.0, GRAY);
+
+  draw_text(&format!("{}", max_value as i32), area.x + PADDING, plot.y + FONT_SIZE * 0.5, FONT_SIZE, GRAY);
+  draw_text("0", area.x + PADDING, plot.y + plot.h, FONT_SIZE, GRAY);
+  draw_text(&language::format(core, "CHART_TURN", &[("turn", min_turn.into())]), plot.x, plot.y + plot.h + FONT_SIZE, FONT_SIZE, GRAY);
+
+  let last_label = language::format(core, "CHART_TURN", &[("turn", max_turn.into())]);
+  let last_width = measure_text(&last_label, None, FONT_SIZE as u16, 1.0).width;
+  draw_text(&last_label, plot.x + plot.w - last_width, plot.y + plot.h + FONT_SIZE, FONT_SIZE, GRAY);
+
+  for series in series {
+    let screen_points: Vec<Vec2> = series.points.iter().map(|(turn, value)| to_screen(*turn, *value)).collect();
+
+    for pair in screen_points.windows(2) {
+      draw_line(pair[0].x, pair[0].y, pair[1].x, pair[1].y, 2.0, series.color);
+    }
+
+    if let Some(last) = screen_points.last() {
+      draw_circle(last.x, last.y, 3.0, series.color);
+    }
+  }
+}
+
+/// Price history for one item: how it moved at the current POI, and how every planet in the system compares.
+/// `name` is the item's translated name
+pub fn draw_price_panel(core: &Core, item: &Item, name: &str, area: Rect) {
+  let Some(state) = &core.state else { return };
+  let planet = &state.planets[state.current_planet as usize];
+  let poi = &planet.poi[state.current_poi as usize];
+  let history: Vec<_> = poi.history.get(&item.id).map(|history| history.iter().copied().collect()).unwrap_or_default();
+
+  let local_height = area.h * 0.3;
+  let local_width = (area.w - PADDING) / 2.;
+
+  draw_line_chart(
+    core,
+    Rect::new(area.x, area.y, local_width, local_height),
+    &language::format(core, "CHART_LOCAL_PRICE", &[("item", name.into()), ("poi", poi.name.as_str().into())]),
+    &[Series {
+      label: language::get(core, "CHART_PRICE"),
+      color: WHITE,
+      points: history.iter().map(|snapshot| (snapshot.turn, snapshot.price)).collect(),
+    }],
+  );
+
+  draw_line_chart(
+    core,
+    Rect::new(area.x + local_width + PADDING, area.y, local_width, local_height),
+    &language::get(core, "CHART_STOCK_AND_DEMAND"),
+    &[
+      Series {
+        label: language::get(core, "CHART_STOCK"),
+        color: SKYBLUE,
+        points: history.iter().map(|snapshot| (snapshot.turn, snapshot.stock)).collect(),
+      },
+      Series {
+        label: language::get(core, "CHART_DEMAND"),
+        color: ORANGE,
+        points: history.iter().map(|snapshot| (snapshot.turn, snapshot.demand)).collect(),
+      },
+    ],
+  );
+
+  let system_top = area.y + local_height + PADDING;
+  let system_height = area.h * 0.45;
+  let system: Vec<Series> = state.planets.iter()
+    .enumerate()
+    .map(|(i, planet)| Series {
+      label: planet.name.clone(),
+      color: PALETTE[i % PALETTE.len()],
+      points: planet.avg_price_history(item.id),
+    })
+    .collect();
+
+  draw_line_chart(
+    core,
+    Rect::new(area.x, system_top, area.w, system_height),
+    &language::get(core, "CHART_SYSTEM"),
+    &system,
+  );
+
+  // Legend, cheapest planet right now first
+  let mut current: Vec<(usize, i32)> = state.planets.iter()
+    .enumerate()
+    .filter_map(|(i, planet)| planet.avg_prices().get(&item.id).map(|price| (i, *price)))
+    .collect();
+  current.sort_by_key(|(_, price)| *price);
+
+  let legend_top = system_top + system_height + FONT_SIZE + PADDING;
+  let columns = 2;
+  let column_width = area.w / columns as f32;
+  let cheapest = language::get(core, "CHART_CHEAPEST");
+  let here = language::get(core, "CHART_HERE");
+
+  for (rank, (i, price)) in current.iter().enumerate() {
+    let x = area.x + (rank % columns) as f32 * column_width;
+    let y = legend_top + (rank / columns) as f32 * (FONT_SIZE + 2.);
+    let notes: String = [(rank == 0, &cheapest), (*i == state.current_planet as usize, &here)].into_iter()
+      .filter(|(applies, _)| *applies)
+      .map(|(_, note)| format!(" {}", note))
+      .collect();
+
+    draw_rectangle(x, y - FONT_SIZE * 0.7, 10., 10., system[*i].color);
+    draw_text(&format!("{}: {}{}", system[*i].label, price, notes), x + 14., y, FONT_SIZE, WHITE);
+  }
+}
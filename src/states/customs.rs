@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+
+use macroquad::{prelude::*, ui::{root_ui, widgets}};
+
+use crate::{components::{core::Core, item::sorted_items}, log, util::{error::GameError, language, save}};
+
+static FONT_SIZE: f32 = 20.0;
+static DIALOG_WIDTH: f32 = 500.0;
+static DIALOG_HEIGHT: f32 = 300.0;
+static BUTTON_WIDTH: f32 = 150.0;
+
+/// Customs found contraband on arrival. The player either pays the fine and loses the goods, or tries a bribe
+pub async fn draw(core: &mut Core) -> Result<(), GameError> {
+  let title = language::get(core, "CUSTOMS_TITLE");
+  let description = language::get(core, "CUSTOMS_DESC");
+  let fine_label = language::get(core, "CUSTOMS_FINE");
+  let bribe_label = language::get(core, "CUSTOMS_BRIBE");
+  let pay_fine = language::get(core, "CUSTOMS_PAY_FINE");
+  let offer_bribe = language::get(core, "CUSTOMS_OFFER_BRIBE");
+  let fine_paid = language::get(core, "CUSTOMS_FINE_PAID");
+  let bribe_taken = language::get(core, "CUSTOMS_BRIBE_TAKEN");
+  let bribe_refused = language::get(core, "CUSTOMS_BRIBE_REFUSED");
+  let cont = language::get(core, "EVENT_CONTINUE");
+  let item_names: HashMap<u32, String> = sorted_items().into_iter()
+    .map(|(id, item)| (*id, language::get(core, item.name_key())))
+    .collect();
+
+  let state = match core.state.as_mut() {
+    Some(state) => state,
+    None => return Err(GameError::NoGame),
+  };
+
+  let scan = match state.pending_scan.clone() {
+    Some(scan) => scan,
+    None => {
+      core.current_stage = crate::states::arrival_stage(state);
+      return Ok(());
+    }
+  };
+
+  let contraband: Vec<String> = scan.contraband.iter()
+    .map(|(id, amount)| {
+      let name = item_names.get(id).cloned().unwrap_or_else(|| id.to_string());
+      format!("{} x{}", name, amount)
+    })
+    .collect();
+
+  // Set once the player has made their choice, the dialog then shows what came of it
+  let mut result: Option<(String, Vec<String>)> = None;
+  let mut status: Option<String> = None;
+
+  loop {
+    clear_background(BLACK);
+
+    let x = (screen_width() - DIALOG_WIDTH) / 2.;
+    let y = (screen_height() - DIALOG_HEIGHT) / 2.;
+    let button_y = y + DIALOG_HEIGHT - FONT_SIZE - 10.;
+
+    draw_rectangle(x, y, DIALOG_WIDTH, DIALOG_HEIGHT, Color::new(0.1, 0.1, 0.15, 1.0));
+    draw_rectangle_lines(x, y, DIALOG_WIDTH, DIALOG_HEIGHT, 2.0, GRAY);
+
+    draw_text(&title, x + 10., y + FONT_SIZE + 4., FONT_SIZE * 1.2, WHITE);
+
+    if let Some((outcome, changes)) = &result {
+      draw_text(outcome, x + 10., y + FONT_SIZE * 2.8, FONT_SIZE * 0.8, LIGHTGRAY);
+
+      // Nothing good ever comes out of customs
+      for (i, change) in changes.iter().enumerate() {
+        draw_text(change, x + 20., y + FONT_SIZE * (4.5 + i as f32), FONT_SIZE * 0.8, RED);
+      }
+
+      let cont_btn = widgets::Button::new(cont.as_str())
+        .position(vec2(x + DIALOG_WIDTH - BUTTON_WIDTH - 10., button_y))
+        .size(vec2(BUTTON_WIDTH, FONT_SIZE))
+        .ui(&mut root_ui());
+
+      if cont_btn {
+        break;
+      }
+    } else {
+      draw_text(&description, x + 10., y + FONT_SIZE * 2.8, FONT_SIZE * 0.8, LIGHTGRAY);
+
+      for (i, line) in contraband.iter().enumerate() {
+        draw_text(line, x + 20., y + FONT_SIZE * (4. + i as f32), FONT_SIZE * 0.8, WHITE);
+      }
+
+      let offset = 4.5 + contraband.len() as f32;
+      draw_text(&format!("{} {}", fine_label, scan.fine), x + 10., y + FONT_SIZE * offset, FONT_SIZE * 0.8, WHITE);
+      draw_text(
+        &format!("{} {} ({:.0}%)", bribe_label, scan.bribe, scan.bribe_chance * 100.),
+        x + 10., y + FONT_SIZE * (offset + 1.), FONT_SIZE * 0.8, WHITE
+      );
+
+      if let Some(status) = &status {
+        draw_text(status, x + 10., button_y - FONT_SIZE * 0.5, FONT_SIZE * 0.8, RED);
+      }
+
+      let fine_btn = widgets::Button::new(pay_fine.as_str())
+        .position(vec2(x + 10., button_y))
+        .size(vec2(BUTTON_WIDTH, FONT_SIZE))
+        .ui(&mut root_ui());
+
+      let bribe_btn = widgets::Button::new(offer_bribe.as_str())
+        .position(vec2(x + 20. + BUTTON_WIDTH, button_y))
+        .size(vec2(BUTTON_WIDTH, FONT_SIZE))
+        .ui(&mut root_ui());
+
+      if fine_btn || bribe_btn {
+        let state = core.state.as_mut().ok_or(GameError::NoGame)?;
+
+        match state.resolve_scan(bribe_btn) {
+          Ok((taken, changes)) => {
+            let outcome = match (bribe_btn, taken) {
+              (false, _) => fine_paid.clone(),
+              (true, true) => bribe_taken.clone(),
+              (true, false) => bribe_refused.clone(),
+            };
+
+            result = Some((outcome, changes.iter().map(|change| language::message(core, change)).collect()));
+          },
+          // Can't afford the bribe, paying the fine is still an option
+          Err(e) => status = Some(language::message(core, &e.message())),
+        }
+      }
+    }
+
+    next_frame().await
+  }
+
+  // Not an autosave, so it ignores the autosave interval. An arrival autosave may have stored the scan
+  // unresolved, and without this a reload would bring it back to be paid or bribed again
+  if let Err(e) = save::save_game(core) {
+    log!("Failed to save the resolved customs scan: {}", e);
+  }
+  core.current_stage = crate::states::Stage::PlanetView;
+
+  Ok(())
+}
@@ -1,21 +1,104 @@
-use macroquad::prelude::*;
+use macroquad::{miniquad::window::clipboard_set, prelude::*, ui::{root_ui, widgets}};
 
-use crate::components::core::Core;
+use crate::{components::core::Core, log, util::{error::GameError, language, path::{log_file_path, open_path}}};
+
+static FONT_SIZE: f32 = 20.0;
+static BUTTON_WIDTH: f32 = 200.0;
+
+/// The error screen can be reached because a language file failed to load, so it can't rely on there being one
+fn text(core: &Core, key: &str, fallback: &str) -> String {
+  language::try_get(core, key).unwrap_or_else(|| fallback.to_string())
+}
+
+/// Everything useful for a bug report, in one block of text
+fn details(core: &Core) -> String {
+  let mut details = format!("Version: {}\nLog: {}\n", core.version, log_file_path().display());
+
+  for error in &core.errors {
+    details.push_str(error);
+    details.push('\n');
+  }
+
+  details
+}
+
+/// Shows the most recent error along with the rest of the session's error history.
+/// Core.errors is never cleared, so going back to the menu and hitting another error shows both.
+pub async fn draw(core: &mut Core) -> Result<(), GameError>  {
+  let title = text(core, "ERROR_TITLE", "An error occurred:");
+  let history = text(core, "ERROR_HISTORY", "Earlier errors:");
+  let log_label = text(core, "ERROR_LOG_PATH", "Log file:");
+  let return_to_menu = text(core, "ERROR_RETURN_TO_MENU", "Return to main menu");
+  let copy_details = text(core, "ERROR_COPY_DETAILS", "Copy details");
+  let open_log = text(core, "ERROR_OPEN_LOG", "Open log");
+  let copied = text(core, "ERROR_COPIED", "Copied to clipboard");
+
+  let log_path = log_file_path();
+  let mut status: Option<String> = None;
 
-pub async fn draw(core: &Core) -> Result<(), std::io::Error>  {
   loop {
     clear_background(BLUE);
 
-    draw_text("An unrecoverable error occurred:", 10.0, 10.0, 20.0, WHITE);
+    draw_text(&title, 10.0, FONT_SIZE, FONT_SIZE, WHITE);
+
+    if let Some((latest, earlier)) = core.errors.split_last() {
+      draw_text(latest, 10.0, FONT_SIZE * 2.5, FONT_SIZE, WHITE);
+
+      if !earlier.is_empty() {
+        draw_text(&history, 10.0, FONT_SIZE * 4., FONT_SIZE, LIGHTGRAY);
+
+        // Newest first, the oldest ones fall off the bottom of the screen
+        for (i, error) in earlier.iter().rev().enumerate() {
+          draw_text(error, 20.0, FONT_SIZE * (5. + i as f32), FONT_SIZE * 0.8, LIGHTGRAY);
+        }
+      }
+    }
+
+    let bottom = screen_height() - FONT_SIZE * 2.;
+
+    draw_text(&format!("{} {}", log_label, log_path.display()), 10.0, bottom - FONT_SIZE * 1.5, FONT_SIZE * 0.8, WHITE);
+
+    if let Some(status) = &status {
+      draw_text(status, 10.0 + (BUTTON_WIDTH + 10.) * 3., bottom + FONT_SIZE * 0.75, FONT_SIZE * 0.8, WHITE);
+    }
+
+    let menu_btn = widgets::Button::new(return_to_menu.as_str())
+      .position(vec2(10., bottom))
+      .size(vec2(BUTTON_WIDTH, FONT_SIZE))
+      .ui(&mut root_ui());
+
+    let copy_btn = widgets::Button::new(copy_details.as_str())
+      .position(vec2(20. + BUTTON_WIDTH, bottom))
+      .size(vec2(BUTTON_WIDTH, FONT_SIZE))
+      .ui(&mut root_ui());
+
+    let log_btn = widgets::Button::new(open_log.as_str())
+      .position(vec2(30. + BUTTON_WIDTH * 2., bottom))
+      .size(vec2(BUTTON_WIDTH, FONT_SIZE))
+      .ui(&mut root_ui());
+
+    if menu_btn {
+      // Whatever was in progress may be half updated, the last autosave is the safe copy
+      core.state = None;
+      core.save_slot = None;
+      core.current_stage = crate::states::Stage::MainMenu;
+      break;
+    }
+
+    if copy_btn {
+      clipboard_set(&details(core));
+      status = Some(copied.clone());
+    }
 
-    // Core.errors is an array, so we should draw all of them
-    for (i, error) in core.errors.iter().enumerate() {
-      let error_xy = vec2(10.0, 40.0 + (i as f32 * 20.0));
-      draw_text(&error, error_xy.x, error_xy.y, 20.0, WHITE);
+    if log_btn {
+      if let Err(e) = open_path(&log_path) {
+        log!("Failed to open log file: {}", e);
+        status = Some(e.to_string());
+      }
     }
 
     next_frame().await
   }
 
   Ok(())
-}
\ No newline at end of file
+}
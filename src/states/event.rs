@@ -0,0 +1,63 @@
+use macroquad::{prelude::*, ui::{root_ui, widgets}};
+
+use crate::{components::core::Core, util::{error::GameError, language}};
+
+static FONT_SIZE: f32 = 20.0;
+static DIALOG_WIDTH: f32 = 500.0;
+static DIALOG_HEIGHT: f32 = 260.0;
+
+/// Dialog for whatever happened on the last jump. The effects are already applied, this just tells the player about them
+pub async fn draw(core: &mut Core) -> Result<(), GameError> {
+  let state = match core.state.as_mut() {
+    Some(state) => state,
+    None => return Err(GameError::NoGame),
+  };
+
+  let event = match state.pending_event.take() {
+    Some(event) => event,
+    None => {
+      core.current_stage = crate::states::arrival_stage(state);
+      return Ok(());
+    }
+  };
+
+  let title = language::get(core, format!("EVENT_{}_TITLE", event.name));
+  let description = language::get(core, format!("EVENT_{}_DESC", event.name));
+  let cont = language::get(core, "EVENT_CONTINUE");
+  let changes: Vec<String> = event.changes.iter().map(|change| language::message(core, change)).collect();
+
+  loop {
+    clear_background(BLACK);
+
+    let x = (screen_width() - DIALOG_WIDTH) / 2.;
+    let y = (screen_height() - DIALOG_HEIGHT) / 2.;
+
+    draw_rectangle(x, y, DIALOG_WIDTH, DIALOG_HEIGHT, Color::new(0.1, 0.1, 0.15, 1.0));
+    draw_rectangle_lines(x, y, DIALOG_WIDTH, DIALOG_HEIGHT, 2.0, GRAY);
+
+    draw_text(&title, x + 10., y + FONT_SIZE + 4., FONT_SIZE * 1.2, WHITE);
+    draw_text(&description, x + 10., y + FONT_SIZE * 2.8, FONT_SIZE * 0.8, LIGHTGRAY);
+
+    for (i, change) in changes.iter().enumerate() {
+      let color = if change.starts_with('+') { GREEN } else { RED };
+      draw_text(change, x + 20., y + FONT_SIZE * (4.5 + i as f32), FONT_SIZE * 0.8, color);
+    }
+
+    let cont_btn = widgets::Button::new(cont.as_str())
+      .position(vec2(x + DIALOG_WIDTH - 130., y + DIALOG_HEIGHT - FONT_SIZE - 10.))
+      .size(vec2(120., FONT_SIZE))
+      .ui(&mut root_ui());
+
+    if cont_btn {
+      break;
+    }
+
+    next_frame().await
+  }
+
+  if let Some(state) = &core.state {
+    core.current_stage = crate::states::arrival_stage(state);
+  }
+
+  Ok(())
+}
@@ -1,13 +1,30 @@
 use macroquad::{prelude::*, ui::{root_ui, widgets}};
 use serde::de;
 
-use crate::components::{core::Core, item::ITEMS};
+use crate::{components::{core::Core, customs::MAX_HEAT}, util::{error::GameError, language, save}};
 
-pub async fn draw_system(core: &mut Core) -> Result<(), std::io::Error> {
-  let state = core.state.as_mut().unwrap();
+use super::{hiring::HiringHall, market::Market, shipyard::Shipyard};
+
+/// Which panel the POI view is showing
+enum PoiTab {
+  Market,
+  Shipyard,
+  Hiring,
+}
+
+pub async fn draw_system(core: &mut Core) -> Result<(), GameError> {
+  let crew_label = language::get(core, "GAME_CREW");
+  // Only change on a jump, which leaves this view
+  let notices: Vec<String> = core.state.as_ref()
+    .map(|state| state.notices.iter().map(|notice| language::message(core, notice)).collect())
+    .unwrap_or_default();
   let mut transition = false;
+  // Turn the jump started on, if there was one
+  let mut travelled = None;
+  let mut status: Option<String> = None;
 
   loop {
+    let state = core.state.as_ref().unwrap();
     let mouse_pos = mouse_position();
     
     clear_background(BLACK);
@@ -18,24 +35,79 @@ pub async fn draw_system(core: &mut Core) -> Result<(), std::io::Error> {
     draw_text(format!("Current planet: {}", state.current_planet).as_str(), 10.0, 30.0, 16.0, WHITE);
     draw_text("Viewing system", 10.0, 40.0, 16.0, WHITE);
     draw_text(format!("Planets in system: {}", state.planets.len()).as_str(), 10.0, 50.0, 16.0, WHITE);
+    draw_text(format!("Turn: {}", state.turn).as_str(), 10.0, 60.0, 16.0, WHITE);
+    draw_text(format!("Seed: {}", state.seed).as_str(), 10.0, 70.0, 16.0, WHITE);
+    draw_text(
+      &language::format(core, "GAME_FUEL", &[("fuel", state.player.ship.fuel.into()), ("max", state.player.ship.effective_spec().max_fuel.into())]),
+      10.0, 80.0, 16.0, WHITE
+    );
+    draw_text(
+      &language::format(core, "GAME_HEAT", &[("heat", state.player.heat.into()), ("max", MAX_HEAT.into())]),
+      10.0, 90.0, 16.0, if state.player.heat > 0 { ORANGE } else { WHITE }
+    );
+
+    if let Some(status) = &status {
+      draw_text(status, 10.0, 100.0, 16.0, RED);
+    }
+
+    // Anything that happened on the last jump, like payday
+    for (i, notice) in notices.iter().enumerate() {
+      draw_text(notice, 10.0, 110.0 + i as f32 * 10., 16.0, YELLOW);
+    }
+
+    let crew_btn = widgets::Button::new(crew_label.as_str())
+      .position(vec2(screen_width() - 110., 0.))
+      .size(vec2(100., 20.))
+      .ui(&mut root_ui());
+
+    if crew_btn {
+      core.current_stage = crate::states::Stage::Roster;
+      return Ok(());
+    }
+
+    let mut destination = None;
 
     // Draw each planet as a circle. It's coordinates are 0-100, a percentage basically
     for (i, planet) in state.planets.iter().enumerate() {
       let x = planet.x as f32 * screen_width() / 100.;
       let y = planet.y as f32 * screen_height() / 100.;
+      let here = i as i32 == state.current_planet;
 
-      draw_circle(x, y, 10.0, WHITE);
+      draw_circle(x, y, 10.0, if here { GREEN } else { WHITE });
 
       if mouse_pos.0 >= x - 10. && mouse_pos.0 <= x + 10. && mouse_pos.1 >= y - 10. && mouse_pos.1 <= y + 10. {
-        if is_mouse_button_down(MouseButton::Left) {
-          core.current_stage = crate::states::Stage::PlanetView;
-          state.current_planet = i as i32;
-          
-          transition = true
+        if is_mouse_button_pressed(MouseButton::Left) {
+          destination = Some(i as i32);
         }
 
-        // Draw the name of the planet to the left of the mouse
+        // Draw the name of the planet to the left of the mouse, with what it'd take to get there
         draw_text(planet.name.as_str(), mouse_pos.0 + 10., mouse_pos.1, 16.0, WHITE);
+
+        if !here {
+          if let Ok((fuel, turns)) = state.travel_cost(i as i32) {
+            let color = if fuel > state.player.ship.fuel { RED } else { GRAY };
+            let cost = language::format(core, "GAME_TRAVEL", &[("fuel", fuel.into()), ("count", turns.into())]);
+            draw_text(&cost, mouse_pos.0 + 10., mouse_pos.1 + 14., 16.0, color);
+          }
+        }
+      }
+    }
+
+    if let Some(planet_id) = destination {
+      // Viewing the planet we're already at is free, flying somewhere else costs fuel and turns
+      if planet_id == state.current_planet {
+        transition = true;
+      } else {
+        let state = core.state.as_mut().unwrap();
+        let turn = state.turn;
+
+        match state.fly_to_planet(planet_id) {
+          Ok(()) => {
+            transition = true;
+            travelled = Some(turn);
+          },
+          Err(e) => status = Some(language::message(core, &e.message())),
+        }
       }
     }
 
@@ -43,17 +115,29 @@ pub async fn draw_system(core: &mut Core) -> Result<(), std::io::Error> {
       break;
     }
 
+    super::draw_overlay(core);
+
     next_frame().await
   }
 
+  if let (Some(from), Some(state)) = (travelled, &core.state) {
+    if save::autosave_due(core.config.autosave_interval, from, state.turn) {
+      save::autosave(core);
+    }
+  }
+
+  if let Some(state) = &core.state {
+    core.current_stage = crate::states::arrival_stage(state);
+  }
+
   Ok(())
 }
 
-pub async fn draw_planet(core: &mut Core) -> Result<(), std::io::Error> {
-  let state = core.state.as_mut().unwrap();
+pub async fn draw_planet(core: &mut Core) -> Result<(), GameError> {
   let mut transition = false;
 
   loop {
+    let state = core.state.as_mut().unwrap();
     let mouse_pos = mouse_position();
 
     clear_background(BLACK);
@@ -100,16 +184,35 @@ pub async fn draw_planet(core: &mut Core) -> Result<(), std::io::Error> {
       break;
     }
 
+    super::draw_overlay(core);
+
     next_frame().await
   }
 
   Ok(())
 }
 
-pub async fn draw_poi(core: &mut Core) -> Result<(), std::io::Error> {
-  let state = core.state.as_ref().unwrap();
+pub async fn draw_poi(core: &mut Core) -> Result<(), GameError> {
+  let mut market = Market::new(core);
+  let mut shipyard = Shipyard::new(core);
+  let mut hiring = HiringHall::new(core);
+  let mut tab = PoiTab::Market;
+  let market_label = language::get(core, "GAME_MARKET");
+  let hiring_label = language::get(core, "GAME_HIRING");
+  let shipyard_label = language::get(core, "GAME_SHIPYARD");
+
+  let (main_type, sub_type) = match &core.state {
+    Some(state) => {
+      let types = &state.planets[state.current_planet as usize].poi[state.current_poi as usize].types;
+      (types.0.clone(), types.1.clone())
+    },
+    None => return Err(GameError::NoGame),
+  };
+  let main_type = format!("{} - {}", language::get(core, main_type.name_key()), language::get(core, &main_type.description));
+  let sub_type = format!("{} - {}", language::get(core, sub_type.name_key()), language::get(core, &sub_type.description));
 
   loop {
+    let state = core.state.as_mut().unwrap();
     clear_background(BLACK);
 
     // Debug core info on screen
@@ -117,8 +220,8 @@ pub async fn draw_poi(core: &mut Core) -> Result<(), std::io::Error> {
     draw_text(format!("Money: {}", state.player.money).as_str(), 10.0, 20.0, 16.0, WHITE);
     draw_text(format!("Current planet: {}", state.current_planet).as_str(), 10.0, 30.0, 16.0, WHITE);
     draw_text(format!("POI: {}", state.planets[state.current_planet as usize].poi[state.current_poi as usize].name).as_str(), 10.0, 40.0, 16.0, WHITE);
-    draw_text(format!("POI Main Type: {}", state.planets[state.current_planet as usize].poi[state.current_poi as usize].types.0.name).as_str(), 10.0, 50.0, 16.0, WHITE);
-    draw_text(format!("POI Sub Type: {}", state.planets[state.current_planet as usize].poi[state.current_poi as usize].types.1.name).as_str(), 10.0, 60.0, 16.0, WHITE);
+    draw_text(format!("POI Main Type: {}", main_type).as_str(), 10.0, 50.0, 16.0, WHITE);
+    draw_text(format!("POI Sub Type: {}", sub_type).as_str(), 10.0, 60.0, 16.0, WHITE);
 
     // Draw crude back button using button and < symbol
     let back_btn = widgets::Button::new("<")
@@ -131,18 +234,33 @@ pub async fn draw_poi(core: &mut Core) -> Result<(), std::io::Error> {
       break;
     }
 
-    // Draw some text to the center of the screen showing the name of the POI and some debug info
     let poi = &state.planets[state.current_planet as usize].poi[state.current_poi as usize];
-    
-    // Draw all of the POIs inventory, as well as demand for that item
-    for (i, inv) in poi.inventory.iter().enumerate() {
-      // demand at index I should be the same item
-      let demand = poi.demand[inv.0];
-      let item = ITEMS.get(inv.0).unwrap();
+    let mut tabs = vec![(PoiTab::Market, market_label.as_str()), (PoiTab::Hiring, hiring_label.as_str())];
+
+    if poi.has_shipyard() {
+      tabs.push((PoiTab::Shipyard, shipyard_label.as_str()));
+    }
+
+    // Tabs run right to left along the top
+    for (i, (target, label)) in tabs.into_iter().enumerate() {
+      let tab_btn = widgets::Button::new(label)
+        .position(vec2(screen_width() - 110. * (i + 1) as f32, 0.))
+        .size(vec2(100., 20.))
+        .ui(&mut root_ui());
 
-      draw_text(format!("{}: {} (Demand: {}%)", item.name, inv.1, demand).as_str(), 240.0, 70.0 + (i as f32 * 12.0), 16.0, WHITE);
+      if tab_btn {
+        tab = target;
+      }
+    }
+
+    match tab {
+      PoiTab::Market => market.draw(core, 80.0),
+      PoiTab::Shipyard => shipyard.draw(core, 80.0),
+      PoiTab::Hiring => hiring.draw(core, 80.0),
     }
 
+    super::draw_overlay(core);
+
     next_frame().await
   }
 
@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+
+use macroquad::{prelude::*, ui::{root_ui, widgets}};
+
+use crate::{components::{core::Core, crew_member::{CrewMember, ALL_JOBS, ALL_TRAITS}}, util::language::{self, Message}};
+
+static ROW_HEIGHT: f32 = 18.0;
+static FONT_SIZE: f32 = 16.0;
+static BUTTON_HEIGHT: f32 = 16.0;
+
+static COL_NAME: f32 = 10.0;
+static COL_JOB: f32 = 200.0;
+static COL_TIER: f32 = 320.0;
+static COL_SALARY: f32 = 380.0;
+static COL_TRAITS: f32 = 460.0;
+static COL_ACTION: f32 = 640.0;
+
+/// Crew for hire at the current POI, along with the crew already aboard
+pub struct HiringHall {
+  /// Translated headers, buttons, and job and trait names by key
+  labels: HashMap<&'static str, String>,
+  /// Result of the last hire or dismissal, shown under the summary
+  status: Option<(Message, Color)>,
+}
+
+impl HiringHall {
+  pub fn new(core: &Core) -> HiringHall {
+    let labels = [
+      "HIRING_JOB", "HIRING_TIER", "HIRING_SALARY", "HIRING_TRAITS", "HIRING_FOR_HIRE", "HIRING_ABOARD", "HIRING_HIRE",
+      "HIRING_DISMISS", "HIRING_NOBODY",
+    ];
+
+    let labels = labels.into_iter()
+      .map(|key| (key, language::get(core, key)))
+      .chain(ALL_JOBS.iter().map(|job| (job.key(), language::get(core, format!("JOB_{}", job.key())))))
+      .chain(ALL_TRAITS.iter().map(|t| (t.key(), language::get(core, format!("TRAIT_{}", t.key())))))
+      .collect();
+
+    HiringHall {
+      labels,
+      status: None,
+    }
+  }
+
+  fn label<'a>(&'a self, key: &'a str) -> &'a str {
+    self.labels.get(key).map(|label| label.as_str()).unwrap_or(key)
+  }
+
+  fn draw_header(&self, y: f32, title: &str) {
+    draw_text(self.label(title), COL_NAME, y, FONT_SIZE, GRAY);
+    draw_text(self.label("HIRING_JOB"), COL_JOB, y, FONT_SIZE, GRAY);
+    draw_text(self.label("HIRING_TIER"), COL_TIER, y, FONT_SIZE, GRAY);
+    draw_text(self.label("HIRING_SALARY"), COL_SALARY, y, FONT_SIZE, GRAY);
+    draw_text(self.label("HIRING_TRAITS"), COL_TRAITS, y, FONT_SIZE, GRAY);
+  }
+
+  /// Draws a crew member's row, returning whether its button was clicked
+  fn draw_row(&self, member: &CrewMember, y: f32, action: &str) -> bool {
+    let text_y = y + BUTTON_HEIGHT - 3.;
+
+    draw_text(&member.name, COL_NAME, text_y, FONT_SIZE, WHITE);
+    draw_text(self.label(member.job.key()), COL_JOB, text_y, FONT_SIZE, WHITE);
+    draw_text(&member.tier.to_string(), COL_TIER, text_y, FONT_SIZE, WHITE);
+    draw_text(&member.salary.to_string(), COL_SALARY, text_y, FONT_SIZE, WHITE);
+
+    let traits: Vec<&str> = member.traits.iter().map(|t| self.label(t.key())).collect();
+    draw_text(&traits.join(", "), COL_TRAITS, text_y, FONT_SIZE, LIGHTGRAY);
+
+    widgets::Button::new(self.label(action))
+      .position(vec2(COL_ACTION, y))
+      .size(vec2(60., BUTTON_HEIGHT))
+      .ui(&mut root_ui())
+  }
+
+  pub fn draw(&mut self, core: &mut Core, top: f32) {
+    let Some(state) = core.state.as_ref() else { return };
+    let payroll: i32 = state.player.ship.crew.iter().map(|member| member.salary).sum();
+    let summary = language::format(core, "HIRING_SUMMARY", &[
+      ("money", state.player.money.into()),
+      ("crew", (state.player.ship.crew.len() as i32).into()),
+      ("max", state.player.ship.effective_spec().max_crew.into()),
+      ("payroll", payroll.into()),
+    ]);
+    let status = self.status.as_ref().map(|(message, color)| (language::message(core, message), *color));
+
+    let Some(state) = core.state.as_mut() else { return };
+    let player = &mut state.player;
+    let poi = &mut state.planets[state.current_planet as usize].poi[state.current_poi as usize];
+
+    draw_text(&summary, COL_NAME, top, FONT_SIZE, WHITE);
+
+    if let Some((status, color)) = &status {
+      draw_text(status, COL_NAME, top + ROW_HEIGHT, FONT_SIZE, *color);
+    }
+
+    // Candidates
+    let header_y = top + ROW_HEIGHT * 2.;
+    self.draw_header(header_y, "HIRING_FOR_HIRE");
+
+    let mut hired = None;
+
+    for (row, candidate) in poi.candidates.iter().enumerate() {
+      if self.draw_row(candidate, header_y + ROW_HEIGHT * (row as f32 + 0.5), "HIRING_HIRE") {
+        hired = Some(row);
+      }
+    }
+
+    if poi.candidates.is_empty() {
+      draw_text(self.label("HIRING_NOBODY"), COL_NAME, header_y + ROW_HEIGHT, FONT_SIZE, GRAY);
+    }
+
+    // Current crew
+    let header_y = header_y + ROW_HEIGHT * (poi.candidates.len().max(1) as f32 + 1.5);
+    self.draw_header(header_y, "HIRING_ABOARD");
+
+    let mut dismissed = None;
+
+    for (row, member) in player.ship.crew.iter().enumerate() {
+      if self.draw_row(member, header_y + ROW_HEIGHT * (row as f32 + 0.5), "HIRING_DISMISS") {
+        dismissed = Some(row);
+      }
+    }
+
+    if let Some(row) = dismissed {
+      let member = player.ship.crew.remove(row);
+      self.status = Some((Message::new("HIRING_DISMISSED", &[("name", member.name.into())]), WHITE));
+    }
+
+    if let Some(row) = hired {
+      let name = poi.candidates[row].name.clone();
+
+      self.status = Some(match state.hire_crew(row) {
+        Ok(()) => (Message::new("HIRING_HIRED", &[("name", name.into())]), GREEN),
+        Err(e) => (e.message(), RED),
+      });
+    }
+  }
+}
@@ -1,8 +1,6 @@
-use std::collections::HashMap;
-
 use macroquad::{prelude::*, ui::{hash, root_ui, widgets}};
 
-use crate::{components::{core::Core, player::Player, ship::{Ship, ShipSpecification}, state::GameState}, log, util::language};
+use crate::{components::{core::Core, player::Player}, log, util::{error::GameError, language}};
 
 struct MenuItem {
   text: String,
@@ -31,12 +29,13 @@ fn mouse_within_text(text_pos: Vec2, text_dimension: TextDimensions) -> bool {
 
 /// The main menu is made up of 4 (future 5) buttons. New Game, Options, Credits, and Quit.
 /// They are drawn on the bottom left of the screen, spaced apart a little bit, stacked on top of each other.
-pub async fn draw(core: &mut Core) -> Result<(), std::io::Error> {
-  let cont = language::get(core, "MENU_CONTINUE")?;
-  let new_game = language::get(core, "MENU_NEW_GAME")?;
-  let options = language::get(core, "MENU_OPTIONS")?;
-  let credits = language::get(core, "MENU_CREDITS")?;
-  let quit = language::get(core, "MENU_QUIT")?;
+pub async fn draw(core: &mut Core) -> Result<(), GameError> {
+  let cont = language::get(core, "MENU_CONTINUE");
+  let new_game = language::get(core, "MENU_NEW_GAME");
+  let options = language::get(core, "MENU_OPTIONS");
+  let mods = language::get(core, "MENU_MODS");
+  let credits = language::get(core, "MENU_CREDITS");
+  let quit = language::get(core, "MENU_QUIT");
 
   let mut br = false;
 
@@ -47,30 +46,23 @@ pub async fn draw(core: &mut Core) -> Result<(), std::io::Error> {
     MenuItem::new(credits, None, |_, _| {
       log!("Unimplemented")
     }),
-    MenuItem::new(options, None, |_, _| {
-      log!("Unimplemented")
+    MenuItem::new(mods, None, |core, br| {
+      core.current_stage = crate::states::Stage::Mods;
+      *br = true;
+    }),
+    MenuItem::new(options, None, |core, br| {
+      core.current_stage = crate::states::Stage::Options;
+      *br = true;
     }),
     MenuItem::new(new_game, None, |core, br| {
-      // TODO send to ship select
-      core.state = Some(GameState::new(
-        "TEST_PLAYER".to_string(),
-        Ship {
-          spec: ShipSpecification {
-            name: "TEST_SHIP".to_string(),
-            max_crew: 10,
-            max_fuel: 100.,
-            max_inventory: 100,
-          },
-          crew: vec![],
-          inventory: HashMap::new(),
-          fuel: 100.,
-        }
-      ));
-
-      core.current_stage = crate::states::Stage::SystemView;
+      // The game itself is created once a ship has been picked
+      core.current_stage = crate::states::Stage::ShipSelect;
+      *br = true;
+    }),
+    MenuItem::new(cont, None, |core, br| {
+      core.current_stage = crate::states::Stage::SaveSelect;
       *br = true;
     }),
-    MenuItem::new(cont, None, |_, _| {}),
   ];
 
   loop {
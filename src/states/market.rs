@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+
+use macroquad::{prelude::*, ui::{root_ui, widgets}};
+
+use crate::{components::{achievement::GameEvent, core::Core, item::{get_item_by_id, sorted_items}}, util::{error::GameError, language::{self, Arg, Message}}};
+
+use super::chart;
+
+static ROW_HEIGHT: f32 = 18.0;
+static FONT_SIZE: f32 = 16.0;
+static BUTTON_HEIGHT: f32 = 16.0;
+
+static COL_NAME: f32 = 10.0;
+static COL_STOCK: f32 = 300.0;
+static COL_DEMAND: f32 = 370.0;
+static COL_PRICE: f32 = 440.0;
+static COL_HELD: f32 = 510.0;
+static COL_QTY: f32 = 570.0;
+static COL_BUY: f32 = 670.0;
+static COL_SELL: f32 = 715.0;
+
+/// Translated text for an item, looked up once when the market opens
+struct ItemText {
+  name: String,
+  category: String,
+  description: String,
+}
+
+/// Trade table for the current POI. Lives for as long as the POI view is open, so quantities
+/// and scroll position survive between frames
+pub struct Market {
+  /// Translated headers and buttons by key
+  labels: HashMap<&'static str, String>,
+  /// Translated name, category and description by item id
+  text: HashMap<u32, ItemText>,
+  /// Selected quantity per item id, defaults to 1
+  quantities: HashMap<u32, i32>,
+  scroll: usize,
+  /// Result of the last trade, shown under the summary
+  status: Option<(Message, Color)>,
+  /// Item whose price history is being shown instead of the table
+  charted: Option<u32>,
+}
+
+impl Market {
+  pub fn new(core: &Core) -> Market {
+    let text = sorted_items().into_iter()
+      .map(|(id, item)| (*id, ItemText {
+        name: language::get(core, item.name_key()),
+        category: language::get(core, item.category_key()),
+        description: language::get(core, &item.description),
+      }))
+      .collect();
+
+    let labels = [
+      "MARKET_BACK", "MARKET_ITEM", "MARKET_STOCK", "MARKET_DEMAND", "MARKET_PRICE", "MARKET_HELD", "MARKET_QTY",
+      "MARKET_BUY", "MARKET_SELL",
+    ];
+
+    Market {
+      labels: labels.into_iter().map(|key| (key, language::get(core, key))).collect(),
+      text,
+      quantities: HashMap::new(),
+      scroll: 0,
+      status: None,
+      charted: None,
+    }
+  }
+
+  fn label<'a>(&'a self, key: &'a str) -> &'a str {
+    self.labels.get(key).map(|label| label.as_str()).unwrap_or(key)
+  }
+
+  fn name(&self, id: u32) -> &str {
+    self.text.get(&id).map(|text| text.name.as_str()).unwrap_or("?")
+  }
+
+  pub fn draw(&mut self, core: &mut Core, top: f32) {
+    let Some(state) = core.state.as_ref() else { return };
+
+    if let Some(item) = self.charted.and_then(|id| get_item_by_id(id as i32)) {
+      let back = widgets::Button::new(self.label("MARKET_BACK"))
+        .position(vec2(COL_NAME, top - BUTTON_HEIGHT + 4.))
+        .size(vec2(120., BUTTON_HEIGHT))
+        .ui(&mut root_ui());
+
+      if back {
+        self.charted = None;
+      }
+
+      let area = Rect::new(COL_NAME, top + ROW_HEIGHT, screen_width() - COL_NAME * 2., screen_height() - top - ROW_HEIGHT * 2.);
+      chart::draw_price_panel(core, &item, self.name(item.id), area);
+      return;
+    }
+
+    let poi = &state.planets[state.current_planet as usize].poi[state.current_poi as usize];
+    let spec = state.player.ship.effective_spec();
+    let summary = language::format(core, if poi.black_market { "MARKET_SUMMARY_BLACK_MARKET" } else { "MARKET_SUMMARY" }, &[
+      ("money", state.player.money.into()),
+      ("cargo", state.player.ship.cargo_used().into()),
+      ("max_cargo", spec.max_inventory.into()),
+      ("fuel", state.player.ship.fuel.into()),
+      ("max_fuel", spec.max_fuel.into()),
+    ]);
+    let refuel_label = language::format(core, "MARKET_REFUEL", &[("price", poi.fuel_price().into())]);
+    let status = self.status.as_ref().map(|(message, color)| (language::message(core, message), *color));
+
+    // Refuelling and trading need the state mutably, so everything translated is formatted above
+    let Some(state) = core.state.as_mut() else { return };
+    let player = &mut state.player;
+    let poi = &mut state.planets[state.current_planet as usize].poi[state.current_poi as usize];
+
+    draw_text(&summary, COL_NAME, top, FONT_SIZE, WHITE);
+
+    let refuel = widgets::Button::new(refuel_label)
+      .position(vec2(COL_QTY, top - BUTTON_HEIGHT + 4.))
+      .size(vec2(COL_SELL + 40. - COL_QTY, BUTTON_HEIGHT))
+      .ui(&mut root_ui());
+
+    if refuel {
+      let money_before = player.money;
+
+      self.status = Some(match poi.refuel(player) {
+        Ok(amount) => (Message::new("MARKET_REFUELED", &[("fuel", amount.into()), ("price", (money_before - player.money).into())]), GREEN),
+        Err(e) => (e.message(), RED),
+      });
+    }
+
+    if let Some((status, color)) = &status {
+      draw_text(status, COL_NAME, top + ROW_HEIGHT, FONT_SIZE, *color);
+    }
+
+    let header_y = top + ROW_HEIGHT * 2.;
+    draw_text(self.label("MARKET_ITEM"), COL_NAME, header_y, FONT_SIZE, GRAY);
+    draw_text(self.label("MARKET_STOCK"), COL_STOCK, header_y, FONT_SIZE, GRAY);
+    draw_text(self.label("MARKET_DEMAND"), COL_DEMAND, header_y, FONT_SIZE, GRAY);
+    draw_text(self.label("MARKET_PRICE"), COL_PRICE, header_y, FONT_SIZE, GRAY);
+    draw_text(self.label("MARKET_HELD"), COL_HELD, header_y, FONT_SIZE, GRAY);
+    draw_text(self.label("MARKET_QTY"), COL_QTY, header_y, FONT_SIZE, GRAY);
+
+    let items = sorted_items();
+    let rows_top = header_y + ROW_HEIGHT;
+    // Last row is kept for the hovered item's description
+    let visible = ((screen_height() - rows_top) / ROW_HEIGHT - 1.).max(1.) as usize;
+    let max_scroll = items.len().saturating_sub(visible);
+
+    let (_, wheel) = mouse_wheel();
+    if wheel < 0. {
+      self.scroll = (self.scroll + 1).min(max_scroll);
+    } else if wheel > 0. {
+      self.scroll = self.scroll.saturating_sub(1);
+    }
+
+    // Holding shift moves quantities in steps of 10
+    let step = if is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift) { 10 } else { 1 };
+    let mut trade = None;
+    let mut hovered = None;
+
+    for (row, (id, item)) in items.iter().skip(self.scroll).take(visible).enumerate() {
+      let y = rows_top + row as f32 * ROW_HEIGHT;
+      let text_y = y + BUTTON_HEIGHT - 3.;
+      let stock = poi.inventory.get(*id).copied().unwrap_or(0);
+      let demand = poi.demand.get(*id).copied().unwrap_or(0);
+      let held = player.ship.cargo_of(**id);
+      let price = poi.calculate_price((*item).clone());
+      let quantity = self.quantities.entry(**id).or_insert(1);
+
+      let name_hovered = Rect::new(COL_NAME, y, COL_STOCK - COL_NAME - 10., ROW_HEIGHT).contains(mouse_position().into());
+
+      if name_hovered {
+        hovered = Some(**id);
+      }
+
+      if name_hovered && is_mouse_button_pressed(MouseButton::Left) {
+        self.charted = Some(**id);
+      }
+
+      let name_color = match (item.illegal, name_hovered) {
+        (_, true) => YELLOW,
+        (true, false) => RED,
+        (false, false) => WHITE,
+      };
+
+      let name = self.text.get(id).map(|text| text.name.as_str()).unwrap_or(&item.name);
+      draw_text(name, COL_NAME, text_y, FONT_SIZE, name_color);
+      draw_text(&stock.to_string(), COL_STOCK, text_y, FONT_SIZE, WHITE);
+      draw_text(&format!("{}%", demand), COL_DEMAND, text_y, FONT_SIZE, WHITE);
+      draw_text(&price.to_string(), COL_PRICE, text_y, FONT_SIZE, WHITE);
+      draw_text(&held.to_string(), COL_HELD, text_y, FONT_SIZE, WHITE);
+
+      let minus = widgets::Button::new("-")
+        .position(vec2(COL_QTY, y))
+        .size(vec2(20., BUTTON_HEIGHT))
+        .ui(&mut root_ui());
+
+      draw_text(&quantity.to_string(), COL_QTY + 26., text_y, FONT_SIZE, WHITE);
+
+      let plus = widgets::Button::new("+")
+        .position(vec2(COL_QTY + 70., y))
+        .size(vec2(20., BUTTON_HEIGHT))
+        .ui(&mut root_ui());
+
+      // Straight from the field, the quantity is still borrowed from self
+      let buy = widgets::Button::new(self.labels["MARKET_BUY"].as_str())
+        .position(vec2(COL_BUY, y))
+        .size(vec2(40., BUTTON_HEIGHT))
+        .ui(&mut root_ui());
+
+      let sell = widgets::Button::new(self.labels["MARKET_SELL"].as_str())
+        .position(vec2(COL_SELL, y))
+        .size(vec2(40., BUTTON_HEIGHT))
+        .ui(&mut root_ui());
+
+      if minus {
+        *quantity = (*quantity - step).max(1);
+      } else if plus {
+        *quantity += step;
+      } else if buy {
+        trade = Some((**id, *quantity, true));
+      } else if sell {
+        trade = Some((**id, *quantity, false));
+      }
+    }
+
+    if let Some(text) = hovered.and_then(|id| self.text.get(&id)) {
+      draw_text(
+        &format!("{} - {}", text.category, text.description),
+        COL_NAME, rows_top + visible as f32 * ROW_HEIGHT + BUTTON_HEIGHT - 3., FONT_SIZE, LIGHTGRAY
+      );
+    }
+
+    if let Some((id, quantity, buying)) = trade {
+      let money_before = player.money;
+      let result = if buying {
+        poi.buy(player, id, quantity)
+      } else {
+        poi.sell(player, id, quantity)
+      };
+
+      // When a trade fails because of a limit, offer the most that would have worked instead
+      let suggestion = match &result {
+        Err(GameError::InsufficientStock { available, .. }) => Some(*available),
+        Err(GameError::CargoFull { free, .. }) => Some(*free),
+        Err(GameError::NotHeld { held, .. }) => Some(*held),
+        Err(GameError::InsufficientFunds { needed, available }) => Some(available / (needed / quantity).max(1)),
+        _ => None,
+      };
+
+      if let Some(suggestion) = suggestion {
+        self.quantities.insert(id, suggestion.max(1));
+      }
+
+      if result.is_ok() {
+        state.events.push(GameEvent::Traded { item_id: id, amount: quantity, buying });
+      }
+
+      self.status = Some(match result {
+        Ok(()) => (Message::new(
+          if buying { "MARKET_BOUGHT" } else { "MARKET_SOLD" },
+          &[("count", quantity.into()), ("item", Arg::Item(id)), ("price", (money_before - player.money).abs().into())]
+        ), GREEN),
+        Err(e) => (e.message(), RED),
+      });
+    }
+  }
+}
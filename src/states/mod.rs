@@ -1,17 +1,71 @@
+use crate::{components::{achievement, core::Core, progress::write_progress, state::GameState}, log, util::language};
+
 #[derive(Debug, Default, PartialEq)]
 pub enum Stage {
   #[default]
   MainMenu,
+  SaveSelect,
   ShipSelect,
+  Options,
+  /// Installed mods and their load order, reached from the main menu
+  Mods,
   SystemView,
   PlanetView,
   POIView,
+  /// Outcome of a random event during travel
+  Event,
+  /// Customs caught contraband on arrival
+  Customs,
+  /// Crew roster, reached from the system view
+  Roster,
 
-  /// Special state, ideally should never happen
+  /// Shown when a fatal error reaches the main loop, the player can head back to the main menu from here
   Error
 }
 
+/// Where to go after arriving at a planet. Anything that happened on the way is dealt with before the planet view
+pub fn arrival_stage(state: &GameState) -> Stage {
+  if state.pending_event.is_some() {
+    Stage::Event
+  } else if state.pending_scan.is_some() {
+    Stage::Customs
+  } else {
+    Stage::PlanetView
+  }
+}
+
+/// Drawn at the end of every frame in the in-game stages. Counts up anything achievements care about,
+/// toasting whatever gets unlocked
+pub fn draw_overlay(core: &mut Core) {
+  if let (Some(progress), Some(state)) = (core.progress.as_mut(), core.state.as_mut()) {
+    if !state.events.is_empty() {
+      let unlocked = achievement::evaluate(progress, state);
+
+      if let Err(e) = write_progress(progress) {
+        log!("Failed to save progress: {}", e);
+      }
+
+      for achievement in unlocked {
+        toast::push(language::get(core, &achievement.title), language::get(core, &achievement.description));
+      }
+    }
+  }
+
+  toast::draw();
+}
+
+pub mod chart;
+pub mod customs;
 pub mod error;
+pub mod event;
 pub mod main_menu;
 pub mod game;
-pub mod ship_select;
\ No newline at end of file
+pub mod hiring;
+pub mod market;
+pub mod mods;
+pub mod options;
+pub mod roster;
+pub mod save_select;
+pub mod ship_select;
+pub mod shipyard;
+pub mod toast;
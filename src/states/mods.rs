@@ -0,0 +1,136 @@
+use macroquad::{prelude::*, ui::{root_ui, widgets}};
+
+use crate::{components::core::Core, log, util::{config::write_config, error::GameError, language::{self, Arg}, mods::{self, Mod}, path::{mods_path, open_path}}};
+
+static FONT_SIZE: f32 = 20.0;
+static ROW_HEIGHT: f32 = 44.0;
+static BUTTON_WIDTH: f32 = 80.0;
+static SMALL_BUTTON_WIDTH: f32 = 24.0;
+
+/// Enabled mods in load order, followed by the disabled ones
+fn ordered(installed: &[Mod], enabled: &[String]) -> Vec<Mod> {
+  let mut ordered: Vec<Mod> = enabled.iter()
+    .filter_map(|id| installed.iter().find(|installed| installed.id == *id).cloned())
+    .collect();
+
+  ordered.extend(installed.iter().filter(|installed| !enabled.contains(&installed.id)).cloned());
+  ordered
+}
+
+/// Installed mods, reached from the main menu. Mods are enabled and ordered here, later mods win any conflicts.
+/// Assets are loaded at startup, so changes only apply after a restart
+pub async fn draw(core: &mut Core) -> Result<(), GameError> {
+  let title = language::get(core, "MODS_TITLE");
+  let restart = language::get(core, "MODS_RESTART");
+  let empty = language::format(core, "MODS_EMPTY", &[("path", Arg::Text(mods_path().display().to_string()))]);
+  let enabled_label = language::get(core, "MODS_ENABLED");
+  let disabled_label = language::get(core, "MODS_DISABLED");
+  let open_folder = language::get(core, "MODS_OPEN_FOLDER");
+
+  let installed = mods::installed();
+  let mut changed = false;
+
+  loop {
+    clear_background(BLACK);
+
+    // Draw crude back button using button and < symbol
+    let back_btn = widgets::Button::new("<")
+      .position(vec2(0., 0.))
+      .size(vec2(20., 20.))
+      .ui(&mut root_ui());
+
+    if back_btn {
+      break;
+    }
+
+    draw_text(&title, 30.0, FONT_SIZE * 1.5, FONT_SIZE * 1.2, WHITE);
+
+    let open_btn = widgets::Button::new(open_folder.as_str())
+      .position(vec2(screen_width() - 160., 0.))
+      .size(vec2(150., 20.))
+      .ui(&mut root_ui());
+
+    if open_btn {
+      let opened = std::fs::create_dir_all(mods_path()).map_err(GameError::from).and_then(|_| open_path(&mods_path()));
+
+      if let Err(e) = opened {
+        log!("Failed to open mods folder: {}", e);
+      }
+    }
+
+    if changed {
+      draw_text(&restart, 30.0, FONT_SIZE * 2.5, FONT_SIZE * 0.8, YELLOW);
+    }
+
+    if installed.is_empty() {
+      draw_text(&empty, 30.0, FONT_SIZE * 4., FONT_SIZE * 0.8, GRAY);
+    }
+
+    let config = &mut core.config;
+    let list = ordered(&installed, &config.mods);
+    let mut new_order: Option<Vec<String>> = None;
+
+    for (i, entry) in list.iter().enumerate() {
+      let y = FONT_SIZE * 3.5 + i as f32 * ROW_HEIGHT;
+      let position = config.mods.iter().position(|id| *id == entry.id);
+
+      let toggle = widgets::Button::new(if position.is_some() { enabled_label.as_str() } else { disabled_label.as_str() })
+        .position(vec2(30., y))
+        .size(vec2(BUTTON_WIDTH, FONT_SIZE))
+        .ui(&mut root_ui());
+
+      // Only enabled mods have a load order to change
+      let (up, down) = match position {
+        Some(_) => (
+          widgets::Button::new("^")
+            .position(vec2(40. + BUTTON_WIDTH, y))
+            .size(vec2(SMALL_BUTTON_WIDTH, FONT_SIZE))
+            .ui(&mut root_ui()),
+          widgets::Button::new("v")
+            .position(vec2(45. + BUTTON_WIDTH + SMALL_BUTTON_WIDTH, y))
+            .size(vec2(SMALL_BUTTON_WIDTH, FONT_SIZE))
+            .ui(&mut root_ui()),
+        ),
+        None => (false, false),
+      };
+
+      let text_x = 60. + BUTTON_WIDTH + SMALL_BUTTON_WIDTH * 2.;
+      let manifest = &entry.manifest;
+      let heading = match (manifest.version.is_empty(), manifest.author.is_empty()) {
+        (true, true) => manifest.name.clone(),
+        (false, true) => format!("{} {}", manifest.name, manifest.version),
+        (true, false) => format!("{} ({})", manifest.name, manifest.author),
+        (false, false) => format!("{} {} ({})", manifest.name, manifest.version, manifest.author),
+      };
+
+      draw_text(&heading, text_x, y + FONT_SIZE * 0.8, FONT_SIZE, if position.is_some() { WHITE } else { GRAY });
+      draw_text(&manifest.description, text_x, y + FONT_SIZE * 1.6, FONT_SIZE * 0.7, LIGHTGRAY);
+
+      let mut order = config.mods.clone();
+
+      match (position, toggle, up, down) {
+        (Some(i), true, _, _) => { order.remove(i); },
+        (None, true, _, _) => order.push(entry.id.clone()),
+        (Some(i), _, true, _) if i > 0 => order.swap(i, i - 1),
+        (Some(i), _, _, true) if i + 1 < order.len() => order.swap(i, i + 1),
+        _ => continue,
+      }
+
+      new_order = Some(order);
+    }
+
+    if let Some(order) = new_order {
+      log!("Mod load order is now: {}", order.join(", "));
+
+      config.mods = order;
+      write_config(config);
+      changed = true;
+    }
+
+    next_frame().await
+  }
+
+  core.current_stage = crate::states::Stage::MainMenu;
+
+  Ok(())
+}
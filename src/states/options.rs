@@ -0,0 +1,221 @@
+use macroquad::{prelude::*, ui::{root_ui, widgets, Skin}};
+
+use crate::{components::core::Core, log, util::{config::{write_config, Config, MAX_FONT_SIZE, MAX_UI_SCALE, MIN_FONT_SIZE, MIN_UI_SCALE, WINDOW_SIZES}, error::GameError, language, logger::{self, LogLevel}}};
+
+static FONT_SIZE: f32 = 20.0;
+static LABEL_WIDTH: f32 = 180.0;
+static ROW_HEIGHT: f32 = 36.0;
+static BUTTON_WIDTH: f32 = 110.0;
+static SMALL_BUTTON_WIDTH: f32 = 30.0;
+
+static UI_SCALE_STEP: f32 = 0.25;
+static FONT_SIZE_STEP: f32 = 2.0;
+/// Autosave intervals on offer, in turns
+static AUTOSAVE_INTERVALS: &[i32] = &[0, 1, 5, 10];
+static LOG_LEVELS: &[LogLevel] = &[LogLevel::Off, LogLevel::Normal, LogLevel::Verbose];
+
+/// Applies everything in the config that can change while the game is running. Language is handled separately,
+/// since it needs the core
+pub fn apply(config: &Config) {
+  request_new_screen_size(config.window_width as f32, config.window_height as f32);
+  set_fullscreen(config.fullscreen);
+  logger::set_level(config.log_level);
+
+  let font_size = (config.font_size * config.ui_scale).round() as u16;
+  let margin = 2. * config.ui_scale;
+  let mut ui = root_ui();
+
+  let label_style = ui.style_builder()
+    .margin(RectOffset::new(margin, margin, margin, margin))
+    .text_color(Color::from_rgba(0, 0, 0, 255))
+    .color_inactive(Color::from_rgba(0, 0, 0, 128))
+    .font_size(font_size)
+    .build();
+
+  let button_style = ui.style_builder()
+    .margin(RectOffset::new(margin, margin, margin, margin))
+    .color(Color::from_rgba(204, 204, 204, 235))
+    .color_clicked(Color::from_rgba(187, 187, 187, 255))
+    .color_hovered(Color::from_rgba(170, 170, 170, 235))
+    .text_color(Color::from_rgba(0, 0, 0, 255))
+    .font_size(font_size)
+    .build();
+
+  let editbox_style = ui.style_builder()
+    .text_color(Color::from_rgba(0, 0, 0, 255))
+    .color_selected(Color::from_rgba(200, 200, 200, 255))
+    .font_size(font_size)
+    .build();
+
+  let skin = Skin {
+    label_style,
+    button_style,
+    editbox_style,
+    ..ui.default_skin()
+  };
+
+  // Only ever one custom skin on the stack, replace it rather than piling them up
+  ui.pop_skin();
+  ui.push_skin(&skin);
+}
+
+/// Draws a label and a row of buttons, returning the index of whichever was clicked. The current choice is marked
+fn draw_choices(label: &str, y: f32, choices: &[String], current: Option<usize>) -> Option<usize> {
+  draw_text(label, 30.0, y + FONT_SIZE * 0.8, FONT_SIZE, WHITE);
+
+  let mut clicked = None;
+
+  for (i, choice) in choices.iter().enumerate() {
+    let text = if current == Some(i) { format!("> {} <", choice) } else { choice.clone() };
+    let btn = widgets::Button::new(text)
+      .position(vec2(LABEL_WIDTH + i as f32 * (BUTTON_WIDTH + 10.), y))
+      .size(vec2(BUTTON_WIDTH, FONT_SIZE))
+      .ui(&mut root_ui());
+
+    if btn {
+      clicked = Some(i);
+    }
+  }
+
+  clicked
+}
+
+/// Draws a label with - and + buttons either side of the value, returning -1 or 1 when one is clicked
+fn draw_stepper(label: &str, y: f32, value: &str) -> i32 {
+  draw_text(label, 30.0, y + FONT_SIZE * 0.8, FONT_SIZE, WHITE);
+
+  let minus = widgets::Button::new("-")
+    .position(vec2(LABEL_WIDTH, y))
+    .size(vec2(SMALL_BUTTON_WIDTH, FONT_SIZE))
+    .ui(&mut root_ui());
+
+  draw_text(value, LABEL_WIDTH + SMALL_BUTTON_WIDTH + 15., y + FONT_SIZE * 0.8, FONT_SIZE, WHITE);
+
+  let plus = widgets::Button::new("+")
+    .position(vec2(LABEL_WIDTH + SMALL_BUTTON_WIDTH + 70., y))
+    .size(vec2(SMALL_BUTTON_WIDTH, FONT_SIZE))
+    .ui(&mut root_ui());
+
+  plus as i32 - minus as i32
+}
+
+/// Options, reached from the main menu. Every change is applied and written to the config straight away
+pub async fn draw(core: &mut Core) -> Result<(), GameError> {
+  let title = language::get(core, "OPTIONS_TITLE");
+  let language_label = language::get(core, "OPTIONS_LANGUAGE");
+  let window_label = language::get(core, "OPTIONS_WINDOW");
+  let fullscreen_label = language::get(core, "OPTIONS_FULLSCREEN");
+  let windowed = language::get(core, "OPTIONS_WINDOWED");
+  let fullscreen = language::get(core, "OPTIONS_FULLSCREEN_ON");
+  let ui_scale_label = language::get(core, "OPTIONS_UI_SCALE");
+  let font_size_label = language::get(core, "OPTIONS_FONT_SIZE");
+  let autosave_label = language::get(core, "OPTIONS_AUTOSAVE");
+  let autosave_off = language::get(core, "OPTIONS_AUTOSAVE_OFF");
+  let turns = language::get(core, "OPTIONS_TURNS");
+  let log_label = language::get(core, "OPTIONS_LOG");
+
+  let log_names = [
+    language::get(core, "OPTIONS_LOG_OFF"),
+    language::get(core, "OPTIONS_LOG_NORMAL"),
+    language::get(core, "OPTIONS_LOG_VERBOSE"),
+  ];
+
+  let languages = language::available_languages();
+  let language_names: Vec<String> = languages.iter().map(|(_, name)| name.clone()).collect();
+  let window_sizes: Vec<String> = WINDOW_SIZES.iter().map(|(width, height)| format!("{}x{}", width, height)).collect();
+  let window_modes = vec![windowed, fullscreen];
+  let autosaves: Vec<String> = AUTOSAVE_INTERVALS.iter()
+    .map(|interval| if *interval == 0 { autosave_off.clone() } else { format!("{} {}", interval, turns) })
+    .collect();
+
+  let mut language_changed = false;
+
+  loop {
+    clear_background(BLACK);
+
+    // Draw crude back button using button and < symbol
+    let back_btn = widgets::Button::new("<")
+      .position(vec2(0., 0.))
+      .size(vec2(20., 20.))
+      .ui(&mut root_ui());
+
+    if back_btn {
+      break;
+    }
+
+    draw_text(&title, 30.0, FONT_SIZE * 1.5, FONT_SIZE * 1.2, WHITE);
+
+    let config = &mut core.config;
+    let mut changed = false;
+    let mut y = FONT_SIZE * 3.;
+
+    let current = languages.iter().position(|(code, _)| *code == config.language);
+    if let Some(i) = draw_choices(&language_label, y, &language_names, current) {
+      config.language = languages[i].0.clone();
+      language_changed = true;
+      changed = true;
+    }
+
+    y += ROW_HEIGHT;
+    let current = WINDOW_SIZES.iter().position(|size| *size == (config.window_width, config.window_height));
+    if let Some(i) = draw_choices(&window_label, y, &window_sizes, current) {
+      (config.window_width, config.window_height) = WINDOW_SIZES[i];
+      changed = true;
+    }
+
+    y += ROW_HEIGHT;
+    if let Some(i) = draw_choices(&fullscreen_label, y, &window_modes, Some(config.fullscreen as usize)) {
+      config.fullscreen = i == 1;
+      changed = true;
+    }
+
+    y += ROW_HEIGHT;
+    let step = draw_stepper(&ui_scale_label, y, &format!("{:.2}x", config.ui_scale));
+    if step != 0 {
+      config.ui_scale = (config.ui_scale + step as f32 * UI_SCALE_STEP).clamp(MIN_UI_SCALE, MAX_UI_SCALE);
+      changed = true;
+    }
+
+    y += ROW_HEIGHT;
+    let step = draw_stepper(&font_size_label, y, &format!("{:.0}", config.font_size));
+    if step != 0 {
+      config.font_size = (config.font_size + step as f32 * FONT_SIZE_STEP).clamp(MIN_FONT_SIZE, MAX_FONT_SIZE);
+      changed = true;
+    }
+
+    y += ROW_HEIGHT;
+    let current = AUTOSAVE_INTERVALS.iter().position(|interval| *interval == config.autosave_interval);
+    if let Some(i) = draw_choices(&autosave_label, y, &autosaves, current) {
+      config.autosave_interval = AUTOSAVE_INTERVALS[i];
+      changed = true;
+    }
+
+    y += ROW_HEIGHT;
+    let current = LOG_LEVELS.iter().position(|level| *level == config.log_level);
+    if let Some(i) = draw_choices(&log_label, y, &log_names, current) {
+      config.log_level = LOG_LEVELS[i];
+      changed = true;
+    }
+
+    if changed {
+      apply(config);
+      write_config(config);
+    }
+
+    next_frame().await
+  }
+
+  // The menu text was fetched in the old language, so it's only swapped once we leave
+  if language_changed {
+    let previous = std::mem::replace(&mut core.language_file, core.config.language.clone());
+
+    if let Err(e) = language::try_load_language(core) {
+      log!("Failed to load language {}: {}", core.language_file, e);
+      core.language_file = previous;
+    }
+  }
+
+  core.current_stage = crate::states::Stage::MainMenu;
+
+  Ok(())
+}
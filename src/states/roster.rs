@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+
+use macroquad::{prelude::*, ui::{root_ui, widgets}};
+
+use crate::{components::{core::Core, crew_member::{ALL_JOBS, ALL_TRAITS, MAX_MORALE, MAX_TIER}}, util::{error::GameError, language}};
+
+static FONT_SIZE: f32 = 20.0;
+static LIST_WIDTH: f32 = 180.0;
+static ROW_HEIGHT: f32 = 22.0;
+static BUTTON_WIDTH: f32 = 150.0;
+
+/// Everyone aboard, with their morale, experience and history. Promotions are settled here
+pub async fn draw(core: &mut Core) -> Result<(), GameError> {
+  let title = language::get(core, "ROSTER_TITLE");
+  let empty = language::get(core, "ROSTER_EMPTY");
+  let job_label = language::get(core, "ROSTER_JOB");
+  let tier_label = language::get(core, "ROSTER_TIER");
+  let salary_label = language::get(core, "ROSTER_SALARY");
+  let morale_label = language::get(core, "ROSTER_MORALE");
+  let experience_label = language::get(core, "ROSTER_EXPERIENCE");
+  let traits_label = language::get(core, "ROSTER_TRAITS");
+  let no_traits = language::get(core, "ROSTER_NO_TRAITS");
+  let promotion_due = language::get(core, "ROSTER_PROMOTION_DUE");
+  let accept = language::get(core, "ROSTER_ACCEPT");
+  let counter = language::get(core, "ROSTER_COUNTER");
+  let refuse = language::get(core, "ROSTER_REFUSE");
+  let history_label = language::get(core, "ROSTER_HISTORY");
+  let turn_label = language::get(core, "ROSTER_TURN");
+
+  let job_names: HashMap<_, _> = ALL_JOBS.iter()
+    .map(|job| (job.key(), language::get(core, format!("JOB_{}", job.key()))))
+    .collect();
+  let trait_names: HashMap<_, _> = ALL_TRAITS.iter()
+    .map(|t| (t.key(), language::get(core, format!("TRAIT_{}", t.key()))))
+    .collect();
+
+  if core.state.is_none() {
+    return Err(GameError::NoGame);
+  }
+
+  let mut selected = 0;
+  let mut status: Option<(String, Color)> = None;
+
+  loop {
+    clear_background(BLACK);
+
+    // Draw crude back button using button and < symbol
+    let back_btn = widgets::Button::new("<")
+      .position(vec2(0., 0.))
+      .size(vec2(20., 20.))
+      .ui(&mut root_ui());
+
+    if back_btn {
+      break;
+    }
+
+    draw_text(&title, 30.0, FONT_SIZE, FONT_SIZE, WHITE);
+
+    let state = core.state.as_ref().ok_or(GameError::NoGame)?;
+    let crew = &state.player.ship.crew;
+
+    if crew.is_empty() {
+      draw_text(&empty, 30.0, FONT_SIZE * 3., FONT_SIZE * 0.8, GRAY);
+      next_frame().await;
+      continue;
+    }
+
+    selected = selected.min(crew.len() - 1);
+
+    // Crew list down the left
+    for (i, member) in crew.iter().enumerate() {
+      let label = if i == selected { format!("> {}", member.name) } else { member.name.clone() };
+      let member_btn = widgets::Button::new(label)
+        .position(vec2(10., FONT_SIZE * 2. + ROW_HEIGHT * i as f32))
+        .size(vec2(LIST_WIDTH, FONT_SIZE))
+        .ui(&mut root_ui());
+
+      if member_btn {
+        selected = i;
+        status = None;
+      }
+    }
+
+    // Details of whoever's selected
+    let member = &crew[selected];
+    let x = LIST_WIDTH + 40.;
+    let mut y = FONT_SIZE * 2.5;
+
+    draw_text(&member.name, x, y, FONT_SIZE * 1.2, WHITE);
+    y += ROW_HEIGHT * 1.5;
+
+    let traits = if member.traits.is_empty() {
+      no_traits.clone()
+    } else {
+      member.traits.iter().map(|t| trait_names[t.key()].as_str()).collect::<Vec<_>>().join(", ")
+    };
+
+    let experience = if member.tier >= MAX_TIER {
+      member.experience.to_string()
+    } else {
+      format!("{}/{}", member.experience, member.promotion_threshold())
+    };
+
+    let details = [
+      (format!("{} {}", job_label, job_names[member.job.key()]), WHITE),
+      (format!("{} {}/{}", tier_label, member.tier, MAX_TIER), WHITE),
+      (format!("{} {}", salary_label, member.salary), WHITE),
+      (format!("{} {}/{}", morale_label, member.morale, MAX_MORALE), if member.morale < MAX_MORALE / 3 { RED } else { WHITE }),
+      (format!("{} {}", experience_label, experience), WHITE),
+      (format!("{} {}", traits_label, traits), WHITE),
+    ];
+
+    for (text, color) in details {
+      draw_text(&text, x, y, FONT_SIZE * 0.8, color);
+      y += ROW_HEIGHT;
+    }
+
+    // Promotion talks. Accepting pays what they ask, countering meets them halfway
+    let mut decision = None;
+
+    if member.due_promotion() {
+      let demand = member.promotion_salary();
+      let halfway = (member.salary + demand) / 2;
+
+      y += ROW_HEIGHT * 0.5;
+      draw_text(&format!("{} {}", promotion_due, demand), x, y, FONT_SIZE * 0.8, YELLOW);
+      y += ROW_HEIGHT * 0.5;
+
+      let accept_btn = widgets::Button::new(format!("{} ({})", accept, demand))
+        .position(vec2(x, y))
+        .size(vec2(BUTTON_WIDTH, FONT_SIZE))
+        .ui(&mut root_ui());
+
+      let counter_btn = widgets::Button::new(format!("{} ({})", counter, halfway))
+        .position(vec2(x + BUTTON_WIDTH + 10., y))
+        .size(vec2(BUTTON_WIDTH, FONT_SIZE))
+        .ui(&mut root_ui());
+
+      let refuse_btn = widgets::Button::new(refuse.as_str())
+        .position(vec2(x + (BUTTON_WIDTH + 10.) * 2., y))
+        .size(vec2(BUTTON_WIDTH, FONT_SIZE))
+        .ui(&mut root_ui());
+
+      if accept_btn {
+        decision = Some(Some(demand));
+      } else if counter_btn {
+        decision = Some(Some(halfway));
+      } else if refuse_btn {
+        decision = Some(None);
+      }
+
+      y += ROW_HEIGHT * 1.5;
+    }
+
+    if let Some((status, color)) = &status {
+      draw_text(status, x, y, FONT_SIZE * 0.8, *color);
+    }
+
+    y += ROW_HEIGHT * 1.5;
+
+    // History, newest first
+    draw_text(&history_label, x, y, FONT_SIZE * 0.8, GRAY);
+
+    for entry in member.history.iter().rev() {
+      y += ROW_HEIGHT;
+      draw_text(&format!("{} {}: {}", turn_label, entry.turn, language::message(core, &entry.message)), x + 10., y, FONT_SIZE * 0.8, LIGHTGRAY);
+    }
+
+    if let Some(offer) = decision {
+      let name = member.name.clone();
+      let state = core.state.as_mut().ok_or(GameError::NoGame)?;
+      let result = match offer {
+        Some(offer) => state.promote_crew(selected, offer),
+        None => state.refuse_promotion(selected),
+      };
+      let recorded = state.player.ship.crew[selected].history.last().map(|entry| entry.message.clone());
+
+      status = Some(match result {
+        Ok(()) => (recorded.map_or(name, |message| language::message(core, &message)), GREEN),
+        Err(e) => (language::message(core, &e.message()), RED),
+      });
+    }
+
+    next_frame().await
+  }
+
+  // Promotions go out with the next autosave on turn advance, like trades do
+  core.current_stage = crate::states::Stage::SystemView;
+
+  Ok(())
+}
@@ -0,0 +1,94 @@
+use chrono::{Local, TimeZone};
+use macroquad::{prelude::*, ui::{root_ui, widgets}};
+
+use crate::{components::core::Core, log, util::{error::GameError, language, save}};
+
+static FONT_SIZE: f32 = 24.0;
+static ROW_WIDTH: f32 = 600.0;
+
+/// Lists every save slot, newest first. Clicking a slot loads it, the X next to it deletes it.
+pub async fn draw(core: &mut Core) -> Result<(), GameError> {
+  let title = language::get(core, "SAVE_SELECT_TITLE");
+  let empty = language::get(core, "SAVE_SELECT_EMPTY");
+  let turn = language::get(core, "SAVE_SELECT_TURN");
+  let credits = language::get(core, "SAVE_SELECT_CREDITS");
+
+  let mut saves = save::list_saves();
+  let mut selected = None;
+
+  loop {
+    clear_background(BLACK);
+
+    // Draw crude back button using button and < symbol
+    let back_btn = widgets::Button::new("<")
+      .position(vec2(0., 0.))
+      .size(vec2(20., 20.))
+      .ui(&mut root_ui());
+
+    if back_btn {
+      core.current_stage = crate::states::Stage::MainMenu;
+      break;
+    }
+
+    draw_text(&title, 30.0, FONT_SIZE, FONT_SIZE, WHITE);
+
+    if saves.is_empty() {
+      draw_text(&empty, 30.0, FONT_SIZE * 3., FONT_SIZE, GRAY);
+    }
+
+    let mut deleted = None;
+
+    for (i, meta) in saves.iter().enumerate() {
+      let y = FONT_SIZE * 2. + i as f32 * (FONT_SIZE + 4.);
+      let saved_at = Local.timestamp_opt(meta.timestamp, 0)
+        .single()
+        .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default();
+
+      let text = format!(
+        "{} | {} {} | {} {} | {} (v{})",
+        meta.player_name, turn, meta.turn, meta.money, credits, saved_at, meta.version
+      );
+
+      let load_btn = widgets::Button::new(text)
+        .position(vec2(30., y))
+        .size(vec2(ROW_WIDTH, FONT_SIZE))
+        .ui(&mut root_ui());
+
+      let delete_btn = widgets::Button::new("X")
+        .position(vec2(34. + ROW_WIDTH, y))
+        .size(vec2(FONT_SIZE, FONT_SIZE))
+        .ui(&mut root_ui());
+
+      if load_btn {
+        selected = Some(meta.slot.clone());
+      } else if delete_btn {
+        deleted = Some(i);
+      }
+    }
+
+    if let Some(i) = deleted {
+      let meta = saves.remove(i);
+
+      if let Err(e) = save::delete_save(&meta.slot) {
+        log!("Failed to delete save {}: {}", meta.slot, e);
+      }
+    }
+
+    if selected.is_some() {
+      break;
+    }
+
+    next_frame().await
+  }
+
+  if let Some(slot) = selected {
+    save::load_game(core, slot)?;
+
+    // A customs scan that was saved unresolved is still waiting
+    let scanned = core.state.as_ref().is_some_and(|state| state.pending_scan.is_some());
+    core.current_stage = if scanned { crate::states::Stage::Customs } else { crate::states::Stage::SystemView };
+  }
+
+  Ok(())
+}
@@ -1,10 +1,188 @@
-use macroquad::prelude::*;
+use macroquad::{prelude::*, ui::{hash, root_ui, widgets}};
 
-use crate::components::core::Core;
+use crate::{components::{core::Core, ship::{sorted_ships, Ship}, state::GameState}, util::{error::GameError, language, random::random_seed, save}};
 
-pub async fn draw(core: &Core) -> Result<(), std::io::Error> {
-  // TODO only show unlocked ships or something idk yet
-  
+static FONT_SIZE: f32 = 20.0;
+static LABEL_WIDTH: f32 = 130.0;
+static ROW_HEIGHT: f32 = 22.0;
+static BUTTON_WIDTH: f32 = 90.0;
+static MAX_NAME_LENGTH: usize = 24;
+
+/// Money the player can start with. Whatever the ship doesn't cost is theirs to trade with
+static BUDGETS: &[i32] = &[2_000, 5_000, 10_000, 20_000];
+
+/// Start of a new game. The player names themselves, picks a budget and buys their first ship out of it.
+/// Ships are laid out side by side so their stats can be compared, locked ones are greyed out.
+pub async fn draw(core: &mut Core) -> Result<(), GameError> {
+  let title = language::get(core, "SHIP_SELECT_TITLE");
+  let name_label = language::get(core, "SHIP_SELECT_NAME");
+  let budget_label = language::get(core, "SHIP_SELECT_BUDGET");
+  let start = language::get(core, "SHIP_SELECT_START");
+  let select = language::get(core, "SHIP_SELECT_SELECT");
+  let locked = language::get(core, "SHIP_SELECT_LOCKED");
+  let need_name = language::get(core, "SHIP_SELECT_NEED_NAME");
+  let too_expensive = language::get(core, "SHIP_SELECT_TOO_EXPENSIVE");
+  let remaining = language::get(core, "SHIP_SELECT_REMAINING");
+
+  let stats = [
+    language::get(core, "SHIP_SELECT_PRICE"),
+    language::get(core, "SHIP_SELECT_FUEL"),
+    language::get(core, "SHIP_SELECT_CARGO"),
+    language::get(core, "SHIP_SELECT_CREW"),
+    language::get(core, "SHIP_SELECT_SPEED"),
+    language::get(core, "SHIP_SELECT_HULL"),
+    language::get(core, "SHIP_SELECT_EFFICIENCY"),
+  ];
+
+  let ships = sorted_ships();
+  let unlocked: Vec<bool> = ships.iter().map(|ship| ship.unlocked(core.progress.as_ref())).collect();
+  let ship_names: Vec<String> = ships.iter().map(|ship| language::get(core, ship.name_key())).collect();
+
+  let mut name = String::new();
+  let mut budget = BUDGETS[1];
+  // Cheapest ship is picked to begin with
+  let mut selected = 0;
+  let mut started = false;
+
+  loop {
+    clear_background(BLACK);
+
+    // Draw crude back button using button and < symbol
+    let back_btn = widgets::Button::new("<")
+      .position(vec2(0., 0.))
+      .size(vec2(20., 20.))
+      .ui(&mut root_ui());
+
+    if back_btn {
+      core.current_stage = crate::states::Stage::MainMenu;
+      break;
+    }
+
+    draw_text(&title, 30.0, FONT_SIZE, FONT_SIZE, WHITE);
+
+    // Name and budget
+    draw_text(&name_label, 30.0, FONT_SIZE * 2.5, FONT_SIZE, WHITE);
+    widgets::InputText::new(hash!())
+      .position(vec2(LABEL_WIDTH, FONT_SIZE * 1.7))
+      .size(vec2(250., FONT_SIZE + 4.))
+      .ui(&mut root_ui(), &mut name);
+
+    name.truncate(MAX_NAME_LENGTH);
+
+    draw_text(&budget_label, 30.0, FONT_SIZE * 4., FONT_SIZE, WHITE);
+
+    for (i, amount) in BUDGETS.iter().enumerate() {
+      let label = if *amount == budget { format!("> {} <", amount) } else { amount.to_string() };
+      let budget_btn = widgets::Button::new(label)
+        .position(vec2(LABEL_WIDTH + i as f32 * (BUTTON_WIDTH + 10.), FONT_SIZE * 3.2))
+        .size(vec2(BUTTON_WIDTH, FONT_SIZE))
+        .ui(&mut root_ui());
+
+      if budget_btn {
+        budget = *amount;
+      }
+    }
+
+    // Catalogue, one column per ship
+    let top = FONT_SIZE * 6.;
+    let column_width = (screen_width() - LABEL_WIDTH - 30.) / ships.len().max(1) as f32;
+
+    for (row, stat) in stats.iter().enumerate() {
+      draw_text(stat, 30.0, top + ROW_HEIGHT * (row + 1) as f32, FONT_SIZE * 0.8, LIGHTGRAY);
+    }
+
+    for (i, ship) in ships.iter().enumerate() {
+      let x = LABEL_WIDTH + i as f32 * column_width;
+      let color = if !unlocked[i] { DARKGRAY } else if ship.price > budget { RED } else { WHITE };
+
+      if i == selected {
+        draw_rectangle_lines(x - 4., top - ROW_HEIGHT, column_width - 4., ROW_HEIGHT * (stats.len() as f32 + 3.), 2.0, GREEN);
+      }
+
+      draw_text(&ship_names[i], x, top, FONT_SIZE * 0.8, color);
+
+      let values = [
+        ship.price.to_string(),
+        format!("{:.0}", ship.max_fuel),
+        ship.max_inventory.to_string(),
+        ship.max_crew.to_string(),
+        format!("{:.2}x", ship.speed),
+        ship.hull.to_string(),
+        format!("{:.2}x", ship.fuel_efficiency),
+      ];
+
+      for (row, value) in values.iter().enumerate() {
+        draw_text(value, x, top + ROW_HEIGHT * (row + 1) as f32, FONT_SIZE * 0.8, color);
+      }
+
+      let button_y = top + ROW_HEIGHT * (values.len() as f32 + 0.5);
+
+      if unlocked[i] {
+        let select_btn = widgets::Button::new(select.as_str())
+          .position(vec2(x, button_y))
+          .size(vec2((column_width - 12.).min(BUTTON_WIDTH), FONT_SIZE))
+          .ui(&mut root_ui());
+
+        if select_btn {
+          selected = i;
+        }
+      } else {
+        draw_text(&locked, x, button_y + FONT_SIZE * 0.75, FONT_SIZE * 0.8, DARKGRAY);
+      }
+    }
+
+    // Summary and start
+    let bottom = screen_height() - FONT_SIZE * 2.;
+    let ship = ships.get(selected);
+    let problem = match ship {
+      _ if name.trim().is_empty() => Some(need_name.clone()),
+      Some(_) if !unlocked[selected] => Some(locked.clone()),
+      Some(ship) if ship.price > budget => Some(too_expensive.clone()),
+      Some(_) => None,
+      None => Some(too_expensive.clone()),
+    };
+
+    match (&problem, ship) {
+      (Some(problem), _) => {
+        draw_text(problem, 30.0, bottom - FONT_SIZE, FONT_SIZE * 0.8, RED);
+      },
+      (None, Some(ship)) => {
+        draw_text(&format!("{} {}", remaining, budget - ship.price), 30.0, bottom - FONT_SIZE, FONT_SIZE * 0.8, WHITE);
+      },
+      _ => {},
+    }
+
+    let start_btn = widgets::Button::new(start.as_str())
+      .position(vec2(30., bottom))
+      .size(vec2(BUTTON_WIDTH * 1.5, FONT_SIZE))
+      .ui(&mut root_ui());
+
+    if start_btn && problem.is_none() {
+      started = true;
+      break;
+    }
+
+    next_frame().await
+  }
+
+  if started {
+    let spec = ships[selected].clone();
+    let name = name.trim().to_string();
+    let money = budget - spec.price;
+
+    let mut state = GameState::new(name.clone(), Ship::new(spec), random_seed());
+    state.player.money = money;
+
+    core.state = Some(state);
+    core.save_slot = Some(save::new_slot_name(&name));
+
+    // Saved straight away so the new game can be continued, unless autosaves are turned off
+    if core.config.autosave_interval > 0 {
+      save::autosave(core);
+    }
+
+    core.current_stage = crate::states::Stage::SystemView;
+  }
 
   Ok(())
-}
\ No newline at end of file
+}
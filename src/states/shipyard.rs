@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+
+use macroquad::{prelude::*, ui::{root_ui, widgets}};
+
+use crate::{components::{core::Core, ship::sorted_ships, shipyard::{buy_ship, buy_upgrade, ship_cost, sorted_upgrades, trade_in_value, UpgradeStat}}, util::language::{self, Arg, Message}};
+
+static ROW_HEIGHT: f32 = 18.0;
+static FONT_SIZE: f32 = 16.0;
+static BUTTON_HEIGHT: f32 = 16.0;
+
+static COL_NAME: f32 = 10.0;
+static COL_PRICE: f32 = 150.0;
+static COL_COST: f32 = 230.0;
+static COL_FUEL: f32 = 310.0;
+static COL_CARGO: f32 = 370.0;
+static COL_CREW: f32 = 430.0;
+static COL_SPEED: f32 = 480.0;
+static COL_HULL: f32 = 540.0;
+static COL_EFFICIENCY: f32 = 600.0;
+static COL_BUY: f32 = 670.0;
+
+/// Ships for sale and upgrades for the current one. Shown in place of the market at POIs with a shipyard
+pub struct Shipyard {
+  /// Translated headers and buttons by key
+  labels: HashMap<&'static str, String>,
+  ship_names: HashMap<u32, String>,
+  upgrade_names: HashMap<u32, String>,
+  /// Result of the last purchase, shown under the summary
+  status: Option<(Message, Color)>,
+}
+
+impl Shipyard {
+  pub fn new(core: &Core) -> Shipyard {
+    let labels = [
+      "SHIPYARD_SHIP", "SHIPYARD_PRICE", "SHIPYARD_COST", "SHIPYARD_FUEL", "SHIPYARD_CARGO", "SHIPYARD_CREW",
+      "SHIPYARD_SPEED", "SHIPYARD_HULL", "SHIPYARD_EFFICIENCY", "SHIPYARD_BUY", "SHIPYARD_UPGRADE", "SHIPYARD_EFFECT",
+      "SHIPYARD_FITTED", "SHIPYARD_FIT",
+    ];
+
+    Shipyard {
+      labels: labels.into_iter().map(|key| (key, language::get(core, key))).collect(),
+      ship_names: sorted_ships().into_iter().map(|ship| (ship.id, language::get(core, ship.name_key()))).collect(),
+      upgrade_names: sorted_upgrades().into_iter().map(|upgrade| (upgrade.id, language::get(core, upgrade.name_key()))).collect(),
+      status: None,
+    }
+  }
+
+  fn label<'a>(&'a self, key: &'a str) -> &'a str {
+    self.labels.get(key).map(|label| label.as_str()).unwrap_or(key)
+  }
+
+  pub fn draw(&mut self, core: &mut Core, top: f32) {
+    let Some(state) = core.state.as_ref() else { return };
+    let player = &state.player;
+    let spec = player.ship.effective_spec();
+
+    draw_text(
+      &language::format(core, "SHIPYARD_SUMMARY", &[
+        ("money", player.money.into()),
+        ("ship", Arg::Key(spec.name_key())),
+        ("cargo", player.ship.cargo_used().into()),
+        ("max_cargo", spec.max_inventory.into()),
+        ("crew", (player.ship.crew.len() as i32).into()),
+        ("max_crew", spec.max_crew.into()),
+        ("trade_in", trade_in_value(&player.ship).into()),
+      ]),
+      COL_NAME, top, FONT_SIZE, WHITE
+    );
+
+    if let Some((status, color)) = &self.status {
+      draw_text(&language::message(core, status), COL_NAME, top + ROW_HEIGHT, FONT_SIZE, *color);
+    }
+
+    // Ships
+    let header_y = top + ROW_HEIGHT * 2.;
+    draw_text(self.label("SHIPYARD_SHIP"), COL_NAME, header_y, FONT_SIZE, GRAY);
+    draw_text(self.label("SHIPYARD_PRICE"), COL_PRICE, header_y, FONT_SIZE, GRAY);
+    draw_text(self.label("SHIPYARD_COST"), COL_COST, header_y, FONT_SIZE, GRAY);
+    draw_text(self.label("SHIPYARD_FUEL"), COL_FUEL, header_y, FONT_SIZE, GRAY);
+    draw_text(self.label("SHIPYARD_CARGO"), COL_CARGO, header_y, FONT_SIZE, GRAY);
+    draw_text(self.label("SHIPYARD_CREW"), COL_CREW, header_y, FONT_SIZE, GRAY);
+    draw_text(self.label("SHIPYARD_SPEED"), COL_SPEED, header_y, FONT_SIZE, GRAY);
+    draw_text(self.label("SHIPYARD_HULL"), COL_HULL, header_y, FONT_SIZE, GRAY);
+    draw_text(self.label("SHIPYARD_EFFICIENCY"), COL_EFFICIENCY, header_y, FONT_SIZE, GRAY);
+
+    let ships = sorted_ships();
+    let mut bought_ship = None;
+
+    for (row, ship) in ships.iter().enumerate() {
+      let y = header_y + ROW_HEIGHT * (row as f32 + 0.5);
+      let text_y = y + BUTTON_HEIGHT - 3.;
+      let current = ship.id == player.ship.spec.id;
+      let cost = ship_cost(player, ship);
+      let color = if current { GREEN } else if cost > player.money { GRAY } else { WHITE };
+
+      draw_text(self.ship_names.get(&ship.id).unwrap_or(&ship.name), COL_NAME, text_y, FONT_SIZE, color);
+      draw_text(&ship.price.to_string(), COL_PRICE, text_y, FONT_SIZE, color);
+      draw_text(&cost.to_string(), COL_COST, text_y, FONT_SIZE, color);
+      draw_text(&format!("{:.0}", ship.max_fuel), COL_FUEL, text_y, FONT_SIZE, color);
+      draw_text(&ship.max_inventory.to_string(), COL_CARGO, text_y, FONT_SIZE, color);
+      draw_text(&ship.max_crew.to_string(), COL_CREW, text_y, FONT_SIZE, color);
+      draw_text(&format!("{:.2}x", ship.speed), COL_SPEED, text_y, FONT_SIZE, color);
+      draw_text(&ship.hull.to_string(), COL_HULL, text_y, FONT_SIZE, color);
+      draw_text(&format!("{:.2}x", ship.fuel_efficiency), COL_EFFICIENCY, text_y, FONT_SIZE, color);
+
+      if current {
+        continue;
+      }
+
+      let buy = widgets::Button::new(self.label("SHIPYARD_BUY"))
+        .position(vec2(COL_BUY, y))
+        .size(vec2(40., BUTTON_HEIGHT))
+        .ui(&mut root_ui());
+
+      if buy {
+        bought_ship = Some(*ship);
+      }
+    }
+
+    // Upgrades
+    let header_y = header_y + ROW_HEIGHT * (ships.len() as f32 + 1.5);
+    draw_text(self.label("SHIPYARD_UPGRADE"), COL_NAME, header_y, FONT_SIZE, GRAY);
+    draw_text(self.label("SHIPYARD_PRICE"), COL_PRICE, header_y, FONT_SIZE, GRAY);
+    draw_text(self.label("SHIPYARD_EFFECT"), COL_COST, header_y, FONT_SIZE, GRAY);
+    draw_text(self.label("SHIPYARD_FITTED"), COL_CARGO, header_y, FONT_SIZE, GRAY);
+
+    let mut bought_upgrade = None;
+
+    for (row, upgrade) in sorted_upgrades().iter().enumerate() {
+      let y = header_y + ROW_HEIGHT * (row as f32 + 0.5);
+      let text_y = y + BUTTON_HEIGHT - 3.;
+      let fitted = player.ship.upgrades.get(&upgrade.id).copied().unwrap_or(0);
+      let effect = match upgrade.stat {
+        UpgradeStat::Fuel => "SHIPYARD_EFFECT_FUEL",
+        UpgradeStat::Inventory => "SHIPYARD_EFFECT_CARGO",
+        UpgradeStat::Crew => "SHIPYARD_EFFECT_CREW",
+      };
+      let color = if fitted >= upgrade.max || upgrade.price > player.money { GRAY } else { WHITE };
+
+      draw_text(self.upgrade_names.get(&upgrade.id).unwrap_or(&upgrade.name), COL_NAME, text_y, FONT_SIZE, color);
+      draw_text(&upgrade.price.to_string(), COL_PRICE, text_y, FONT_SIZE, color);
+      draw_text(&language::format(core, effect, &[("amount", upgrade.amount.into())]), COL_COST, text_y, FONT_SIZE, color);
+      draw_text(&format!("{}/{}", fitted, upgrade.max), COL_CARGO, text_y, FONT_SIZE, color);
+
+      let buy = widgets::Button::new(self.label("SHIPYARD_FIT"))
+        .position(vec2(COL_BUY, y))
+        .size(vec2(40., BUTTON_HEIGHT))
+        .ui(&mut root_ui());
+
+      if buy {
+        bought_upgrade = Some(*upgrade);
+      }
+    }
+
+    // Only now that drawing is done can the player be borrowed mutably
+    let Some(state) = core.state.as_mut() else { return };
+    let player = &mut state.player;
+
+    if let Some(ship) = bought_ship {
+      let money_before = player.money;
+
+      self.status = Some(match buy_ship(player, ship) {
+        Ok(()) => (Message::new("SHIPYARD_BOUGHT", &[("ship", Arg::Key(ship.name_key())), ("price", (money_before - player.money).into())]), GREEN),
+        Err(e) => (e.message(), RED),
+      });
+    }
+
+    if let Some(upgrade) = bought_upgrade {
+      self.status = Some(match buy_upgrade(player, upgrade) {
+        Ok(()) => (Message::new("SHIPYARD_FITTED_UPGRADE", &[("upgrade", Arg::Key(upgrade.name_key())), ("price", upgrade.price.into())]), GREEN),
+        Err(e) => (e.message(), RED),
+      });
+    }
+  }
+}
@@ -0,0 +1,46 @@
+use std::sync::Mutex;
+
+use macroquad::prelude::*;
+
+static TOASTS: Mutex<Vec<Toast>> = Mutex::new(Vec::new());
+
+static FONT_SIZE: f32 = 18.0;
+static WIDTH: f32 = 300.0;
+static HEIGHT: f32 = 50.0;
+/// How long a toast stays up, in seconds
+static DURATION: f64 = 4.0;
+
+/// Popup in the corner telling the player something, eg. why an action didn't go through or an unlocked achievement
+struct Toast {
+  title: String,
+  text: String,
+  /// Set the first frame it's drawn, so toasts queued behind others still get their full time
+  shown_at: Option<f64>,
+}
+
+pub fn push(title: String, text: String) {
+  TOASTS.lock().unwrap().push(Toast { title, text, shown_at: None });
+}
+
+/// Draws the oldest toast over whatever else is on screen, and drops it once its time is up
+pub fn draw() {
+  let mut toasts = TOASTS.lock().unwrap();
+  let now = get_time();
+
+  toasts.retain(|toast| toast.shown_at.is_none_or(|shown_at| now - shown_at < DURATION));
+
+  let toast = match toasts.first_mut() {
+    Some(toast) => toast,
+    None => return,
+  };
+
+  toast.shown_at.get_or_insert(now);
+
+  let x = screen_width() - WIDTH - 10.;
+  let y = screen_height() - HEIGHT - 10.;
+
+  draw_rectangle(x, y, WIDTH, HEIGHT, Color::new(0.1, 0.1, 0.15, 0.95));
+  draw_rectangle_lines(x, y, WIDTH, HEIGHT, 2.0, GOLD);
+  draw_text(&toast.title, x + 10., y + FONT_SIZE + 2., FONT_SIZE, GOLD);
+  draw_text(&toast.text, x + 10., y + FONT_SIZE * 2. + 4., FONT_SIZE * 0.8, LIGHTGRAY);
+}
@@ -1,15 +1,70 @@
 use serde::{Deserialize, Serialize};
-use super::path::config_path;
+use super::{logger::LogLevel, path::config_path};
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Window sizes offered in the options, the first is the default
+pub static WINDOW_SIZES: &[(i32, i32)] = &[(800, 600), (1280, 720), (1600, 900), (1920, 1080)];
+pub static MIN_UI_SCALE: f32 = 0.5;
+pub static MAX_UI_SCALE: f32 = 2.0;
+pub static MIN_FONT_SIZE: f32 = 10.0;
+pub static MAX_FONT_SIZE: f32 = 32.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
   pub language: String,
+  #[serde(default = "default_window_width")]
+  pub window_width: i32,
+  #[serde(default = "default_window_height")]
+  pub window_height: i32,
+  #[serde(default)]
+  pub fullscreen: bool,
+  /// Multiplies the size of buttons and text boxes
+  #[serde(default = "default_ui_scale")]
+  pub ui_scale: f32,
+  /// Text size in buttons and text boxes, before the UI scale
+  #[serde(default = "default_font_size")]
+  pub font_size: f32,
+  /// Turns between autosaves while travelling, 0 only saves when it has to (eg. after customs)
+  #[serde(default = "default_autosave_interval")]
+  pub autosave_interval: i32,
+  #[serde(default)]
+  pub log_level: LogLevel,
+  /// Enabled mods by folder name, in load order. Later mods win conflicts
+  #[serde(default)]
+  pub mods: Vec<String>,
+}
+
+fn default_window_width() -> i32 {
+  WINDOW_SIZES[0].0
+}
+
+fn default_window_height() -> i32 {
+  WINDOW_SIZES[0].1
+}
+
+fn default_ui_scale() -> f32 {
+  1.0
+}
+
+fn default_font_size() -> f32 {
+  16.0
+}
+
+fn default_autosave_interval() -> i32 {
+  1
 }
 
 impl Default for Config {
   fn default() -> Self {
     Config {
       language: "en".to_string(),
+      window_width: default_window_width(),
+      window_height: default_window_height(),
+      fullscreen: false,
+      ui_scale: default_ui_scale(),
+      font_size: default_font_size(),
+      autosave_interval: default_autosave_interval(),
+      log_level: LogLevel::default(),
+      mods: Vec::new(),
     }
   }
 }
@@ -30,3 +85,16 @@ pub fn write_config(config: &Config) {
   serde_json::to_writer(file, config).unwrap();
 }
 
+#[cfg(test)]
+mod tests {
+  use super::Config;
+
+  #[test]
+  fn old_configs_get_defaults() {
+    let config: Config = serde_json::from_str(r#"{"language":"en"}"#).unwrap();
+
+    assert_eq!(config.window_width, Config::default().window_width);
+    assert_eq!(config.autosave_interval, 1);
+    assert_eq!(config.ui_scale, 1.0);
+  }
+}
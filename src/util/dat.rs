@@ -0,0 +1,193 @@
+use std::{fs, str::FromStr};
+
+use super::{error::GameError, path::asset_path};
+
+/// One whitespace separated field, and the 1-based column it starts at
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+  pub column: usize,
+  pub value: String,
+}
+
+/// A line of a .dat file that had something other than comments on it
+#[derive(Debug, Clone)]
+pub struct Record {
+  file: String,
+  pub line: usize,
+  pub fields: Vec<Field>,
+}
+
+impl Record {
+  pub fn len(&self) -> usize {
+    self.fields.len()
+  }
+
+  pub fn get(&self, index: usize) -> Option<&str> {
+    self.fields.get(index).map(|field| field.value.as_str())
+  }
+
+  /// Error about the line as a whole
+  pub fn error(&self, message: String) -> GameError {
+    GameError::AssetParse {
+      file: self.file.clone(),
+      line: self.line,
+      column: None,
+      message,
+    }
+  }
+
+  /// Error pointing at one field, or at the line when it doesn't have that many
+  pub fn field_error(&self, index: usize, message: String) -> GameError {
+    GameError::AssetParse {
+      file: self.file.clone(),
+      line: self.line,
+      column: self.fields.get(index).map(|field| field.column),
+      message,
+    }
+  }
+
+  pub fn expect_len(&self, len: usize) -> Result<(), GameError> {
+    if self.len() == len {
+      Ok(())
+    } else {
+      Err(self.error(format!("Expected {} fields, found {}", len, self.len())))
+    }
+  }
+
+  /// A field that has to be there, `name` is only used in the error
+  pub fn text(&self, index: usize, name: &str) -> Result<&str, GameError> {
+    self.get(index).ok_or_else(|| self.error(format!("Missing {}", name)))
+  }
+
+  pub fn parse<T: FromStr>(&self, index: usize, name: &str) -> Result<T, GameError> {
+    let value = self.text(index, name)?;
+    value.parse().map_err(|_| self.field_error(index, format!("Invalid {}: {}", name, value)))
+  }
+
+  /// TRUE or FALSE, in any case
+  pub fn flag(&self, index: usize, name: &str) -> Result<bool, GameError> {
+    let value = self.text(index, name)?;
+    value.to_lowercase().parse().map_err(|_| self.field_error(index, format!("Invalid {}: {}", name, value)))
+  }
+}
+
+/// Splits a .dat file into records. Fields are separated by whitespace, double quotes keep a field with spaces
+/// in it together (with \" \\ \n and \t escapes), and # starts a comment anywhere outside of quotes.
+/// Blank and comment-only lines are skipped
+pub fn parse(file: &str, contents: &str) -> Result<Vec<Record>, GameError> {
+  let mut records = Vec::new();
+
+  for (i, line) in contents.lines().enumerate() {
+    let error = |column: usize, message: &str| GameError::AssetParse {
+      file: file.to_string(),
+      line: i + 1,
+      column: Some(column),
+      message: message.to_string(),
+    };
+
+    let mut fields = Vec::new();
+    let mut chars = line.chars().enumerate().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+      if c == '#' {
+        break;
+      }
+
+      if c.is_whitespace() {
+        chars.next();
+        continue;
+      }
+
+      let mut value = String::new();
+
+      if c == '"' {
+        chars.next();
+
+        loop {
+          match chars.next() {
+            Some((_, '"')) => break,
+            Some((column, '\\')) => match chars.next() {
+              Some((_, '"')) => value.push('"'),
+              Some((_, '\\')) => value.push('\\'),
+              Some((_, 'n')) => value.push('\n'),
+              Some((_, 't')) => value.push('\t'),
+              _ => return Err(error(column + 1, "Invalid escape in quoted field")),
+            },
+            Some((_, c)) => value.push(c),
+            None => return Err(error(start + 1, "Quoted field is never closed")),
+          }
+        }
+
+        if chars.peek().is_some_and(|(_, c)| !c.is_whitespace() && *c != '#') {
+          return Err(error(start + 1, "Quoted field runs into the next one"));
+        }
+      } else {
+        while let Some(&(_, c)) = chars.peek() {
+          if c.is_whitespace() || c == '#' {
+            break;
+          }
+
+          value.push(c);
+          chars.next();
+        }
+      }
+
+      fields.push(Field { column: start + 1, value });
+    }
+
+    if !fields.is_empty() {
+      records.push(Record { file: file.to_string(), line: i + 1, fields });
+    }
+  }
+
+  Ok(records)
+}
+
+/// Reads and splits one of the game's own .dat assets
+pub fn read(file: &str) -> Result<Vec<Record>, GameError> {
+  parse(file, &fs::read_to_string(asset_path().join(file))?)
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::util::error::GameError;
+
+  use super::parse;
+
+  fn values(contents: &str) -> Vec<Vec<String>> {
+    parse("test.dat", contents).unwrap()
+      .into_iter()
+      .map(|record| record.fields.into_iter().map(|field| field.value).collect())
+      .collect()
+  }
+
+  #[test]
+  fn splits_fields_quotes_and_comments() {
+    let parsed = values("# header\n\n0  GOLD \"Shiny, \\\"heavy\\\" metal\" # inline\n  1 \"\" TRUE#tail");
+
+    assert_eq!(parsed, vec![
+      vec!["0".to_string(), "GOLD".to_string(), "Shiny, \"heavy\" metal".to_string()],
+      vec!["1".to_string(), String::new(), "TRUE".to_string()],
+    ]);
+  }
+
+  #[test]
+  fn records_know_their_line_and_columns() {
+    let records = parse("test.dat", "# header\n0  GOLD \"a b\" x").unwrap();
+
+    assert_eq!(records[0].line, 2);
+    assert_eq!(records[0].fields.iter().map(|field| field.column).collect::<Vec<_>>(), vec![1, 4, 9, 15]);
+  }
+
+  #[test]
+  fn bad_quotes_are_errors() {
+    let column = |contents: &str| match parse("test.dat", contents) {
+      Err(GameError::AssetParse { column, .. }) => column,
+      other => panic!("expected a parse error, got {:?}", other.map(|records| records.len())),
+    };
+
+    assert_eq!(column("0 \"open"), Some(3));
+    assert_eq!(column("0 \"bad \\q\""), Some(8));
+    assert_eq!(column("0 \"a\"b"), Some(3));
+  }
+}
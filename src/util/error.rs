@@ -0,0 +1,124 @@
+use std::fmt::Display;
+
+use crate::components::item::get_item_by_id;
+
+use super::language::{Arg, Message};
+
+#[derive(Debug)]
+pub enum GameError {
+  /// Player can't afford a purchase
+  InsufficientFunds { needed: i32, available: i32 },
+  /// POI doesn't have enough of an item to sell
+  InsufficientStock { item_id: u32, requested: i32, available: i32 },
+  /// Ship's hold can't fit the goods
+  CargoFull { requested: i32, free: i32 },
+  /// Player tried to sell more than they're carrying
+  NotHeld { item_id: u32, requested: i32, held: i32 },
+  /// Ship doesn't have quarters for everyone aboard
+  CrewFull { crew: i32, max: i32 },
+  /// Ship already has as many of an upgrade as it can take, `upgrade` is its language key
+  UpgradeLimit { upgrade: String, max: i32 },
+  /// Crew member hasn't earned enough experience to be promoted
+  NotDueForPromotion(String),
+  /// Not enough fuel in the tank for a jump
+  InsufficientFuel { needed: f32, available: f32 },
+  /// Tried to refuel with a full tank
+  TankFull,
+  InvalidAmount(i32),
+  UnknownItem(u32),
+  UnknownPlanet(i32),
+  /// An action that needs a game in progress was attempted without one
+  NoGame,
+  MissingLanguageKey(String),
+  /// A line in one of the .dat assets couldn't be understood. Column is 1-based, when the problem is with one field
+  AssetParse { file: String, line: usize, column: Option<usize>, message: String },
+  /// Save was written by a newer build than the one running
+  SaveTooNew { save: String, build: String },
+  /// Save exists but can't be used, eg. it's corrupt or a migration failed
+  Save(String),
+  Io(std::io::Error),
+  Json(serde_json::Error),
+}
+
+impl GameError {
+  /// Recoverable errors are reported where they happen and play continues.
+  /// Everything else ends up on the error screen.
+  pub fn is_recoverable(&self) -> bool {
+    matches!(
+      self,
+      GameError::InsufficientFunds { .. }
+        | GameError::InsufficientStock { .. }
+        | GameError::CargoFull { .. }
+        | GameError::NotHeld { .. }
+        | GameError::CrewFull { .. }
+        | GameError::UpgradeLimit { .. }
+        | GameError::NotDueForPromotion(_)
+        | GameError::InsufficientFuel { .. }
+        | GameError::TankFull
+        | GameError::InvalidAmount(_)
+    )
+  }
+
+  /// What to tell the player, in their language. Errors that only end up on the error screen or in the log
+  /// stay in English
+  pub fn message(&self) -> Message {
+    match self {
+      GameError::InsufficientFunds { needed, available } => Message::new("ERROR_INSUFFICIENT_FUNDS", &[("needed", (*needed).into()), ("available", (*available).into())]),
+      GameError::InsufficientStock { item_id, requested, available } => Message::new("ERROR_INSUFFICIENT_STOCK", &[("item", Arg::Item(*item_id)), ("requested", (*requested).into()), ("available", (*available).into())]),
+      GameError::CargoFull { requested, free } => Message::new("ERROR_CARGO_FULL", &[("requested", (*requested).into()), ("free", (*free).into())]),
+      GameError::NotHeld { item_id, requested, held } => Message::new("ERROR_NOT_HELD", &[("item", Arg::Item(*item_id)), ("requested", (*requested).into()), ("held", (*held).into())]),
+      GameError::CrewFull { crew, max } => Message::new("ERROR_CREW_FULL", &[("crew", (*crew).into()), ("max", (*max).into())]),
+      GameError::UpgradeLimit { upgrade, max } => Message::new("ERROR_UPGRADE_LIMIT", &[("upgrade", Arg::Key(upgrade.clone())), ("max", (*max).into())]),
+      GameError::NotDueForPromotion(name) => Message::new("ERROR_NOT_DUE_FOR_PROMOTION", &[("name", name.as_str().into())]),
+      GameError::InsufficientFuel { needed, available } => Message::new("ERROR_INSUFFICIENT_FUEL", &[("needed", (*needed).into()), ("available", (*available).into())]),
+      GameError::TankFull => Message::new("ERROR_TANK_FULL", &[]),
+      GameError::InvalidAmount(amount) => Message::new("ERROR_INVALID_AMOUNT", &[("amount", (*amount).into())]),
+      _ => Message::Text(self.to_string()),
+    }
+  }
+}
+
+fn item_name(item_id: u32) -> String {
+  get_item_by_id(item_id as i32).map(|item| item.name).unwrap_or_else(|| item_id.to_string())
+}
+
+impl Display for GameError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      GameError::InsufficientFunds { needed, available } => write!(f, "Not enough money (need {}, have {})", needed, available),
+      GameError::InsufficientStock { item_id, requested, available } => write!(f, "Not enough {} to sell (wanted {}, only {} in stock)", item_name(*item_id), requested, available),
+      GameError::CargoFull { requested, free } => write!(f, "Not enough cargo space (need {}, {} free)", requested, free),
+      GameError::NotHeld { item_id, requested, held } => write!(f, "Not enough {} (wanted to sell {}, holding {})", item_name(*item_id), requested, held),
+      GameError::CrewFull { crew, max } => write!(f, "Not enough crew quarters (crew of {}, room for {})", crew, max),
+      GameError::UpgradeLimit { upgrade, max } => write!(f, "Can't fit any more {} (limit of {})", upgrade, max),
+      GameError::NotDueForPromotion(name) => write!(f, "{} isn't due a promotion yet", name),
+      GameError::InsufficientFuel { needed, available } => write!(f, "Not enough fuel (need {:.1}, have {:.1})", needed, available),
+      GameError::TankFull => write!(f, "Fuel tank is already full"),
+      GameError::InvalidAmount(amount) => write!(f, "Invalid amount: {}", amount),
+      GameError::UnknownItem(id) => write!(f, "Item not found: {}", id),
+      GameError::UnknownPlanet(id) => write!(f, "Planet not found: {}", id),
+      GameError::NoGame => write!(f, "No game in progress"),
+      GameError::MissingLanguageKey(key) => write!(f, "Language key not found: {}", key),
+      GameError::AssetParse { file, line, column: Some(column), message } => write!(f, "{}:{}:{}: {}", file, line, column, message),
+      GameError::AssetParse { file, line, column: None, message } => write!(f, "{}:{}: {}", file, line, message),
+      GameError::SaveTooNew { save, build } => write!(f, "Save was made with version {}, which is newer than this build ({})", save, build),
+      GameError::Save(message) => write!(f, "Save error: {}", message),
+      GameError::Io(e) => write!(f, "IO error: {}", e),
+      GameError::Json(e) => write!(f, "JSON error: {}", e),
+    }
+  }
+}
+
+impl std::error::Error for GameError {}
+
+impl From<std::io::Error> for GameError {
+  fn from(e: std::io::Error) -> Self {
+    GameError::Io(e)
+  }
+}
+
+impl From<serde_json::Error> for GameError {
+  fn from(e: serde_json::Error) -> Self {
+    GameError::Json(e)
+  }
+}
@@ -61,22 +61,21 @@ pub fn n_to_roman(n: i32) -> String {
   roman::to(n).unwrap_or("".to_string())
 }
 
-pub fn generate_name(params: NameGenerationParams) -> String {
+pub fn generate_name(rng: &mut impl Rng, params: NameGenerationParams) -> String {
   let mut name = String::new();
-  let mut rng = rand::thread_rng();
   let mut length = rng.gen_range(params.min.unwrap_or(DEFAULT_MIN)..params.max.unwrap_or(DEFAULT_MAX));
   let mut first_iter = true;
 
-  let numeral = if params.can_have_numeral.unwrap_or(true) && maybe() || params.must_have_numeral.unwrap_or(false) {
-    Some(rand::random::<i32>() % 20)
+  let numeral = if params.can_have_numeral.unwrap_or(true) && maybe(rng) || params.must_have_numeral.unwrap_or(false) {
+    Some(rng.gen_range(1..20))
   } else {
     None
   };
 
   while length > 0 {
-    let mut phonetic = PHONETICS[rand::random::<usize>() % PHONETICS.len()].to_string();
+    let mut phonetic = PHONETICS[rng.gen_range(0..PHONETICS.len())].to_string();
     // Can only do this if not at the beginning
-    let double_consonant = !first_iter && maybe_chance(4);
+    let double_consonant = !first_iter && maybe_chance(rng, 4);
     
     if double_consonant && !phonetic.starts_with(&['A', 'E', 'I', 'O', 'U'][..]) {
       let first_char = phonetic.chars().next().unwrap();
@@ -95,16 +94,16 @@ pub fn generate_name(params: NameGenerationParams) -> String {
     }
   }
 
-  let end_letter = params.can_have_end_letter.unwrap_or(true) && maybe_chance(4) || params.must_have_end_letter.unwrap_or(false);
+  let end_letter = params.can_have_end_letter.unwrap_or(true) && maybe_chance(rng, 4) || params.must_have_end_letter.unwrap_or(false);
 
   if end_letter {
     // If the last existing letter is a consonant, add a vowel, or vice versa
     let last_char = name.chars().last().unwrap();
 
     let end = if END_CONSONANTS.iter().any(|&c| c == last_char.to_string()) {
-      END_VOWELS[rand::random::<usize>() % END_VOWELS.len()]
+      END_VOWELS[rng.gen_range(0..END_VOWELS.len())]
     } else {
-      END_CONSONANTS[rand::random::<usize>() % END_CONSONANTS.len()]
+      END_CONSONANTS[rng.gen_range(0..END_CONSONANTS.len())]
     };
 
     name.push_str(end);
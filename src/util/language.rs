@@ -1,37 +1,513 @@
-use crate::components::core::Core;
+use std::{collections::BTreeSet, sync::Mutex};
 
-use super::path::lang_path;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
-pub fn try_load_language(core: &mut Core) -> Result<(), std::io::Error> {
+use crate::{components::{core::Core, item::get_item_by_id}, log};
+
+use super::{error::GameError, mods, path::lang_path};
+
+/// Language every other is checked against, and falls back to
+static FALLBACK_LANGUAGE: &str = "en";
+
+/// Keys that have already been warned about, so a missing key drawn every frame is only logged once
+static WARNED: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+/// Reads a language with whatever the active mods add to it layered on top
+fn read_language(code: &str) -> Result<Value, GameError> {
   let path = lang_path();
-  let file = path.join(format!("{}.tr", core.language_file));
-  let file = std::fs::File::open(&file)?;
-  let reader = std::io::BufReader::new(file);
-  let data = serde_json::from_reader(reader)?;
-  
-  core.language = Some(data);
+  let file = path.join(format!("{}.tr", code));
+
+  let mut data = match std::fs::File::open(&file) {
+    Ok(file) => serde_json::from_reader(std::io::BufReader::new(file))?,
+    // Mods can add whole new languages
+    Err(_) if mods::language_codes().iter().any(|mod_code| mod_code == code) => Value::Object(Default::default()),
+    Err(e) => return Err(e.into()),
+  };
 
-  Ok(())
+  mods::layer_language(code, &mut data);
+
+  Ok(data)
 }
 
-pub fn get_language_name(code: impl AsRef<str>) -> Result<String, std::io::Error> {
-  let path = lang_path();
-  let file = path.join(format!("{}.tr", code.as_ref()));
-  let file = std::fs::File::open(&file)?;
-  let reader = std::io::BufReader::new(file);
-  let data: serde_json::Value = serde_json::from_reader(reader)?;
+/// Loads the selected language, along with English to fall back on
+pub fn try_load_language(core: &mut Core) -> Result<(), GameError> {
+  let fallback = if core.language_file == FALLBACK_LANGUAGE {
+    None
+  } else {
+    read_language(FALLBACK_LANGUAGE)
+      .inspect_err(|e| log!("Failed to load fallback language: {}", e))
+      .ok()
+  };
+
+  match read_language(&core.language_file) {
+    Ok(data) => {
+      core.language = Some(data);
+      core.fallback_language = fallback;
+      WARNED.lock().unwrap().clear();
+
+      Ok(())
+    },
+    Err(e) => {
+      // Nothing loaded yet, English is better than raw keys. Otherwise keep whatever was working
+      if core.language.is_none() {
+        core.fallback_language = fallback;
+      }
+
+      Err(e)
+    },
+  }
+}
+
+pub fn get_language_name(code: impl AsRef<str>) -> Result<String, GameError> {
+  let data = read_language(code.as_ref())?;
+
+  match data["LANGUAGE"].as_str() {
+    Some(name) => Ok(name.to_string()),
+    None => Err(GameError::MissingLanguageKey("LANGUAGE".to_string())),
+  }
+}
+
+/// Every language in the lang folder or added by a mod as (code, name), sorted by code. Files without a readable
+/// LANGUAGE name are left out
+pub fn available_languages() -> Vec<(String, String)> {
+  let codes = std::fs::read_dir(lang_path())
+    .into_iter()
+    .flatten()
+    .filter_map(|entry| {
+      let path = entry.ok()?.path();
+
+      if path.extension()? != "tr" {
+        return None;
+      }
+
+      Some(path.file_stem()?.to_str()?.to_string())
+    })
+    .chain(mods::language_codes());
+
+  let mut languages: Vec<(String, String)> = codes
+    .filter_map(|code| {
+      let name = get_language_name(&code).ok()?;
+      Some((code, name))
+    })
+    .collect();
+
+  languages.sort();
+  languages
+}
+
+fn warn_once(key: &str, message: String) {
+  if WARNED.lock().unwrap().insert(key.to_string()) {
+    log!("{}", message);
+  }
+}
+
+/// Finds a key in the selected language, then in English. Gives back the value along with the code of the
+/// language it came from, since plural rules follow whichever language the text is in
+fn lookup<'a>(core: &'a Core, key: &str) -> Option<(&'a Value, &'a str)> {
+  let find = |data: &'a Option<Value>| data.as_ref()
+    .map(|data| &data[key])
+    .filter(|value| value.is_string() || value.is_object());
+
+  if let Some(value) = find(&core.language) {
+    return Some((value, core.language_file.as_str()));
+  }
+
+  let value = find(&core.fallback_language)?;
+  warn_once(key, format!("Language key {} is missing from {}, using English", key, core.language_file));
+
+  Some((value, FALLBACK_LANGUAGE))
+}
+
+/// Looks a key up in the selected language, then in English. Plural keys give back their "other" form
+pub fn try_get(core: &Core, key: impl AsRef<str>) -> Option<String> {
+  let (value, _) = lookup(core, key.as_ref())?;
+
+  value.as_str()
+    .or_else(|| value["other"].as_str())
+    .map(|value| value.to_string())
+}
+
+/// Looks a key up in the selected language, then English, and if neither have it gives back the key itself.
+/// Missing keys are logged the first time they're asked for
+pub fn get(core: &Core, key: impl AsRef<str>) -> String {
+  let key = key.as_ref();
+
+  try_get(core, key).unwrap_or_else(|| {
+    warn_once(key, format!("Language key {} is missing from every language", key));
+    key.to_string()
+  })
+}
+
+/// Keys `language` is missing compared to `reference`, and keys it has that `reference` doesn't, both sorted
+pub fn diff_keys(reference: &Value, language: &Value) -> (Vec<String>, Vec<String>) {
+  let keys = |data: &Value| -> BTreeSet<String> {
+    data.as_object().map(|object| object.keys().cloned().collect()).unwrap_or_default()
+  };
+
+  let reference = keys(reference);
+  let language = keys(language);
+
+  (
+    reference.difference(&language).cloned().collect(),
+    language.difference(&reference).cloned().collect(),
+  )
+}
+
+/// Startup check of every language against English, logging what each is missing or has extra
+pub fn check_languages() {
+  let reference = match read_language(FALLBACK_LANGUAGE) {
+    Ok(reference) => reference,
+    Err(e) => {
+      log!("Can't check languages, failed to load {}: {}", FALLBACK_LANGUAGE, e);
+      return;
+    }
+  };
+
+  for (code, _) in available_languages().into_iter().filter(|(code, _)| code != FALLBACK_LANGUAGE) {
+    let language = match read_language(&code) {
+      Ok(language) => language,
+      Err(e) => {
+        log!("Failed to load language {}: {}", code, e);
+        continue;
+      }
+    };
+
+    let (missing, extra) = diff_keys(&reference, &language);
+
+    if !missing.is_empty() {
+      log!("Language {} is missing {} keys: {}", code, missing.len(), missing.join(", "));
+    }
+
+    if !extra.is_empty() {
+      log!("Language {} has {} keys English doesn't: {}", code, extra.len(), extra.join(", "));
+    }
+  }
+}
+
+/// Value substituted into a translated string by `format`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Arg {
+  Text(String),
+  Int(i64),
+  Float(f64),
+  /// Item id, shown as the item's translated name
+  Item(u32),
+  /// Another language key, shown translated
+  Key(String),
+}
+
+impl From<&str> for Arg {
+  fn from(value: &str) -> Self {
+    Arg::Text(value.to_string())
+  }
+}
+
+impl From<String> for Arg {
+  fn from(value: String) -> Self {
+    Arg::Text(value)
+  }
+}
+
+impl From<i32> for Arg {
+  fn from(value: i32) -> Self {
+    Arg::Int(value as i64)
+  }
+}
+
+impl From<u32> for Arg {
+  fn from(value: u32) -> Self {
+    Arg::Int(value as i64)
+  }
+}
+
+impl From<f32> for Arg {
+  fn from(value: f32) -> Self {
+    Arg::Float(value as f64)
+  }
+}
+
+/// CLDR plural category for `n` in the given language. Only the families we're likely to be translated into,
+/// everything else gets English rules
+fn plural_category(code: &str, n: i64) -> &'static str {
+  let n = n.unsigned_abs();
+  let (tens, ones) = (n % 100, n % 10);
+
+  match code.split(['-', '_']).next().unwrap_or(code) {
+    "ja" | "ko" | "zh" | "vi" | "th" | "id" => "other",
+    "fr" | "pt" => if n <= 1 { "one" } else { "other" },
+    "ru" | "uk" | "be" => match (ones, tens) {
+      (1, t) if t != 11 => "one",
+      (2..=4, t) if !(12..=14).contains(&t) => "few",
+      _ => "many",
+    },
+    "pl" => match (n, ones, tens) {
+      (1, _, _) => "one",
+      (_, 2..=4, t) if !(12..=14).contains(&t) => "few",
+      _ => "many",
+    },
+    "cs" | "sk" => match n {
+      1 => "one",
+      2..=4 => "few",
+      _ => "other",
+    },
+    _ => if n == 1 { "one" } else { "other" },
+  }
+}
+
+/// Formats a number with the language's digit grouping and decimal separator
+fn format_number(core: &Core, value: f64, decimals: usize) -> String {
+  let thousands = try_get(core, "FORMAT_THOUSANDS").unwrap_or_else(|| ",".to_string());
+  let decimal = try_get(core, "FORMAT_DECIMAL").unwrap_or_else(|| ".".to_string());
+
+  let formatted = format!("{:.*}", decimals, value.abs());
+  let (whole, fraction) = formatted.split_once('.').unwrap_or((&formatted, ""));
+
+  let mut grouped = String::new();
+  for (i, digit) in whole.chars().enumerate() {
+    if i > 0 && (whole.len() - i) % 3 == 0 {
+      grouped.push_str(&thousands);
+    }
+    grouped.push(digit);
+  }
+
+  if !fraction.is_empty() {
+    grouped.push_str(&decimal);
+    grouped.push_str(fraction);
+  }
+
+  // Don't leave a -0 behind when a small negative number rounds away
+  if value < 0. && formatted.chars().any(|c| c.is_ascii_digit() && c != '0') {
+    grouped.insert(0, '-');
+  }
+
+  grouped
+}
+
+/// Formats one argument according to the style after the colon in its placeholder, if there was one:
+/// `currency`, or `.N` for N decimal places, either of which can start with `+` to always show the sign.
+/// Integers are grouped and floats get two decimals by default
+fn format_arg(core: &Core, arg: &Arg, style: Option<&str>) -> String {
+  let (signed, style) = match style.and_then(|style| style.strip_prefix('+')) {
+    Some(rest) => (true, Some(rest).filter(|rest| !rest.is_empty())),
+    None => (false, style),
+  };
+  let decimals = style.and_then(|style| style.strip_prefix('.')).and_then(|n| n.parse().ok());
+
+  let mut number = match arg {
+    Arg::Text(text) => return text.clone(),
+    Arg::Item(id) => return get_item_by_id(*id as i32).map_or_else(|| id.to_string(), |item| get(core, item.name_key())),
+    Arg::Key(key) => return get(core, key),
+    Arg::Int(value) => format_number(core, *value as f64, decimals.unwrap_or(0)),
+    Arg::Float(value) => format_number(core, *value, decimals.unwrap_or(2)),
+  };
+
+  if signed && !number.starts_with('-') {
+    number.insert(0, '+');
+  }
+
+  if style == Some("currency") {
+    let template = try_get(core, "FORMAT_CURRENCY").unwrap_or_else(|| "{amount}".to_string());
+    template.replace("{amount}", &number)
+  } else {
+    number
+  }
+}
+
+/// Fills `{name}` and `{name:style}` placeholders from the arguments. `{{` and `}}` are literal braces,
+/// placeholders without a matching argument are left as they are
+fn fill(core: &Core, key: &str, template: &str, args: &[(&str, Arg)]) -> String {
+  let mut result = String::new();
+  let mut chars = template.chars().peekable();
+
+  while let Some(c) = chars.next() {
+    match c {
+      '{' if chars.peek() == Some(&'{') => {
+        chars.next();
+        result.push('{');
+      },
+      '}' if chars.peek() == Some(&'}') => {
+        chars.next();
+        result.push('}');
+      },
+      '{' => {
+        let placeholder: String = chars.by_ref().take_while(|c| *c != '}').collect();
+        let (name, style) = match placeholder.split_once(':') {
+          Some((name, style)) => (name, Some(style)),
+          None => (placeholder.as_str(), None),
+        };
+
+        match args.iter().find(|(arg, _)| *arg == name) {
+          Some((_, arg)) => result.push_str(&format_arg(core, arg, style)),
+          None => {
+            warn_once(&format!("{}:{}", key, name), format!("Language key {} has no argument {}", key, name));
+            result.push_str(&format!("{{{}}}", placeholder));
+          },
+        }
+      },
+      c => result.push(c),
+    }
+  }
+
+  result
+}
+
+/// Looks a key up like `get` and fills its placeholders from the arguments. A key can instead hold an object of
+/// plural forms ("one", "few", "many", "other"...), picked by the integer `count` argument using the rules of
+/// whichever language the text came from
+pub fn format(core: &Core, key: impl AsRef<str>, args: &[(&str, Arg)]) -> String {
+  let key = key.as_ref();
 
-  Ok(data["LANGUAGE"].as_str().unwrap().to_string())
+  let Some((value, code)) = lookup(core, key) else {
+    warn_once(key, format!("Language key {} is missing from every language", key));
+    return key.to_string();
+  };
+
+  let template = match value.as_str() {
+    Some(template) => template,
+    None => {
+      let count = args.iter().find_map(|(name, arg)| match (*name, arg) {
+        ("count", Arg::Int(count)) => Some(*count),
+        _ => None,
+      });
+      let category = plural_category(code, count.unwrap_or(0));
+
+      value[category].as_str()
+        .or_else(|| value["other"].as_str())
+        .unwrap_or(key)
+    },
+  };
+
+  fill(core, key, template, args)
+}
+
+/// A translated string kept as its key and arguments, so it can be formatted in whichever language is selected
+/// when it's shown rather than when it happened
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Message {
+  Key { key: String, args: Vec<(String, Arg)> },
+  /// Already formatted text
+  Text(String),
 }
 
-pub fn get(core: &Core, key: impl AsRef<str>) -> Result<String, std::io::Error> {
-  if let Some(data) = &core.language {
-    let key = key.as_ref();
-    
-    if let Some(value) = data[key].as_str() {
-      return Ok(value.to_string());
+impl Message {
+  pub fn new(key: impl Into<String>, args: &[(&str, Arg)]) -> Self {
+    Message::Key {
+      key: key.into(),
+      args: args.iter().map(|(name, arg)| (name.to_string(), arg.clone())).collect(),
     }
   }
-  
-  Err(std::io::Error::new(std::io::ErrorKind::NotFound, "Language key not found"))
-}
\ No newline at end of file
+}
+
+/// Formats a message in the selected language
+pub fn message(core: &Core, message: &Message) -> String {
+  match message {
+    Message::Key { key, args } => {
+      let args: Vec<(&str, Arg)> = args.iter().map(|(name, arg)| (name.as_str(), arg.clone())).collect();
+      format(core, key, &args)
+    },
+    Message::Text(text) => text.clone(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use crate::components::{core::Core, item::{get_all_items, get_item_by_name}, planet::get_all_poi_types, ship::get_all_ships, shipyard::get_all_upgrades};
+
+  use super::{diff_keys, format, get, message, plural_category, read_language, Arg, Message};
+
+  #[test]
+  fn falls_back_to_english_then_the_key() {
+    let mut core = Core::new();
+
+    core.language = Some(json!({ "GREETING": "Hallo" }));
+    core.fallback_language = Some(json!({ "GREETING": "Hello", "FAREWELL": "Goodbye" }));
+
+    assert_eq!(get(&core, "GREETING"), "Hallo");
+    assert_eq!(get(&core, "FAREWELL"), "Goodbye");
+    assert_eq!(get(&core, "MISSING_KEY"), "MISSING_KEY");
+  }
+
+  #[test]
+  fn diff_finds_missing_and_extra_keys() {
+    let (missing, extra) = diff_keys(&json!({ "A": "", "B": "", "C": "" }), &json!({ "A": "", "D": "" }));
+
+    assert_eq!(missing, vec!["B", "C"]);
+    assert_eq!(extra, vec!["D"]);
+  }
+
+  #[test]
+  fn english_has_its_own_name() {
+    assert!(read_language("en").unwrap()["LANGUAGE"].is_string());
+  }
+
+  #[test]
+  fn formats_placeholders_plurals_and_numbers() {
+    let mut core = Core::new();
+
+    core.language = Some(json!({
+      "FORMAT_THOUSANDS": ".",
+      "FORMAT_DECIMAL": ",",
+      "FORMAT_CURRENCY": "{amount} cr",
+      "BOUGHT": { "one": "{count} unit of {item} for {price:currency}", "other": "{count} units of {item} for {price:currency}" },
+      "FUEL": "{fuel:.1} fuel, {{not a placeholder}} {missing}",
+    }));
+
+    let bought = |count: i32| format(&core, "BOUGHT", &[("count", count.into()), ("item", "Grain".into()), ("price", Arg::Int(1234567))]);
+
+    assert_eq!(bought(1), "1 unit of Grain for 1.234.567 cr");
+    assert_eq!(bought(3), "3 units of Grain for 1.234.567 cr");
+    assert_eq!(format(&core, "FUEL", &[("fuel", 1234.56f32.into())]), "1.234,6 fuel, {not a placeholder} {missing}");
+  }
+
+  #[test]
+  fn plural_rules_follow_the_language() {
+    assert_eq!(plural_category("en", 0), "other");
+    assert_eq!(plural_category("fr", 0), "one");
+    assert_eq!(plural_category("pl", 22), "few");
+    assert_eq!(plural_category("pl", 12), "many");
+    assert_eq!(plural_category("ru", 21), "one");
+    assert_eq!(plural_category("ja", 1), "other");
+  }
+
+  #[test]
+  fn messages_are_formatted_when_shown() {
+    let mut core = Core::new();
+    let grain = get_item_by_name("GRAIN").unwrap().id;
+
+    core.language = Some(json!({ "GAINED": "{amount:+} {item}", "ITEM_GRAIN": "Korn" }));
+
+    let gained = Message::new("GAINED", &[("amount", 5.into()), ("item", Arg::Item(grain))]);
+
+    assert_eq!(message(&core, &gained), "+5 Korn");
+    assert_eq!(message(&core, &Message::new("GAINED", &[("amount", (-5).into()), ("item", "Grain".into())])), "-5 Grain");
+
+    // Saved as key and arguments
+    let saved = serde_json::to_value(&gained).unwrap();
+    assert_eq!(serde_json::from_value::<Message>(saved).unwrap(), gained);
+  }
+
+  #[test]
+  fn english_names_every_asset() {
+    let english = read_language("en").unwrap();
+    let mut keys = Vec::new();
+
+    for item in get_all_items().unwrap() {
+      keys.extend([item.name_key(), item.category_key(), item.description]);
+    }
+
+    for poi_type in get_all_poi_types().unwrap() {
+      keys.extend([poi_type.name_key(), poi_type.description]);
+    }
+
+    keys.extend(get_all_ships().unwrap().iter().map(|ship| ship.name_key()));
+    keys.extend(get_all_upgrades().unwrap().iter().map(|upgrade| upgrade.name_key()));
+
+    for key in keys {
+      assert!(english[&key].is_string(), "en.tr is missing {}", key);
+    }
+  }
+}
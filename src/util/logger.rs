@@ -3,8 +3,30 @@ use std::fmt::Display;
 use std::fs::{self, File};
 use std::io::Write;
 use std::sync::Mutex;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use serde::{Deserialize, Serialize};
 
 static LOG_FILE: Mutex<Option<File>> = Mutex::new(None);
+static LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Normal as u8);
+
+/// How much ends up in the console and log file
+#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub enum LogLevel {
+  Off,
+  #[default]
+  Normal,
+  /// Also logs log_verbose! messages, like every stage change
+  Verbose,
+}
+
+pub fn set_level(level: LogLevel) {
+  LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+fn enabled(level: LogLevel) -> bool {
+  level != LogLevel::Off && LEVEL.load(Ordering::Relaxed) >= level as u8
+}
 
 pub fn init(with_file: bool) {
   if with_file {
@@ -19,6 +41,14 @@ pub fn init(with_file: bool) {
 }
 
 pub fn log(s: impl AsRef<str> + Display) {
+  log_at(LogLevel::Normal, s)
+}
+
+pub fn log_at(level: LogLevel, s: impl AsRef<str> + Display) {
+  if !enabled(level) {
+    return;
+  }
+
   println!("[{}] {}", Local::now().format("%Y-%m-%d %H:%M:%S"), s);
 
   let mut file = LOG_FILE.lock().unwrap();
@@ -34,3 +64,10 @@ macro_rules! log {
     $crate::util::logger::log(format!($($arg)*))
   };
 }
+
+#[macro_export]
+macro_rules! log_verbose {
+  ($($arg:tt)*) => {
+    $crate::util::logger::log_at($crate::util::logger::LogLevel::Verbose, format!($($arg)*))
+  };
+}
@@ -0,0 +1,113 @@
+use serde_json::Value;
+
+use crate::log;
+
+use super::error::GameError;
+
+type Migration = fn(&mut Value) -> Result<(), GameError>;
+
+/// Saves from before the envelope existed have no top level version, they're treated as this
+static UNVERSIONED: &str = "0.0.0";
+
+/// Each entry upgrades a save written by an older game version to the layout expected by `version`.
+/// Must stay sorted by version. Purely additive fields should use #[serde(default)] instead of a migration.
+static MIGRATIONS: &[(&str, Migration)] = &[
+  ("0.1.0", add_envelope_version),
+];
+
+/// Splits a version like "1.2.3" (or "1.2.3-beta") into comparable parts
+fn parse_version(version: &str) -> Vec<u32> {
+  version.split(['.', '-'])
+    .map_while(|part| part.parse().ok())
+    .collect()
+}
+
+pub fn save_version(doc: &Value) -> String {
+  doc["version"].as_str().unwrap_or(UNVERSIONED).to_string()
+}
+
+/// Upgrades a raw save document in place, step by step, until it matches `current` (Core::version)
+pub fn migrate(doc: &mut Value, current: &str) -> Result<(), GameError> {
+  if !doc.is_object() {
+    return Err(GameError::Save("Save is not a JSON object".to_string()));
+  }
+
+  let version = save_version(doc);
+  let from = parse_version(&version);
+  let to = parse_version(current);
+
+  if from > to {
+    return Err(GameError::SaveTooNew { save: version, build: current.to_string() });
+  }
+
+  for (target, migration) in MIGRATIONS {
+    let target_version = parse_version(target);
+
+    if from < target_version && target_version <= to {
+      log!("Migrating save from {} to {}", save_version(doc), target);
+      migration(doc)?;
+      doc["version"] = Value::String(target.to_string());
+    }
+  }
+
+  doc["version"] = Value::String(current.to_string());
+
+  Ok(())
+}
+
+/// 0.1.0: saves gained a top level version so it can be checked before the state is parsed.
+/// The version itself is stamped by migrate(), so all that's left is making sure this is actually a save.
+fn add_envelope_version(doc: &mut Value) -> Result<(), GameError> {
+  if !doc["meta"].is_object() || !doc["state"].is_object() {
+    return Err(GameError::Save("Save is missing its metadata or game state".to_string()));
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use crate::util::error::GameError;
+
+  use super::{migrate, parse_version, save_version};
+
+  #[test]
+  fn versions_compare_part_by_part() {
+    assert_eq!(parse_version("1.2.3-beta"), vec![1, 2, 3]);
+    assert!(parse_version("0.10.0") > parse_version("0.9.0"));
+    assert!(parse_version("0.1") < parse_version("0.1.0"));
+    assert!(parse_version("1.0.0") > parse_version("0.99.99"));
+  }
+
+  #[test]
+  fn unversioned_saves_are_brought_up_to_date() {
+    let mut doc = json!({ "meta": {}, "state": {} });
+
+    migrate(&mut doc, "0.1.0").unwrap();
+
+    assert_eq!(save_version(&doc), "0.1.0");
+  }
+
+  #[test]
+  fn unversioned_saves_need_meta_and_state() {
+    let mut doc = json!({ "state": {} });
+
+    assert!(matches!(migrate(&mut doc, "0.1.0"), Err(GameError::Save(_))));
+  }
+
+  #[test]
+  fn newer_saves_are_refused() {
+    let mut doc = json!({ "version": "0.2.0", "meta": {}, "state": {} });
+
+    assert!(matches!(migrate(&mut doc, "0.1.0"), Err(GameError::SaveTooNew { .. })));
+    assert_eq!(save_version(&doc), "0.2.0");
+  }
+
+  #[test]
+  fn only_objects_are_saves() {
+    assert!(matches!(migrate(&mut json!([1, 2, 3]), "0.1.0"), Err(GameError::Save(_))));
+    assert!(matches!(migrate(&mut json!("save"), "0.1.0"), Err(GameError::Save(_))));
+  }
+}
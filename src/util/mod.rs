@@ -1,6 +1,11 @@
 pub mod config;
+pub mod dat;
+pub mod error;
 pub mod generators;
 pub mod language;
 pub mod logger;
+pub mod migrate;
+pub mod mods;
 pub mod path;
-pub mod random;
\ No newline at end of file
+pub mod random;
+pub mod save;
\ No newline at end of file
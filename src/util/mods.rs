@@ -0,0 +1,221 @@
+use std::{collections::HashMap, fs, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{log, log_verbose};
+
+use super::{config::read_config, dat::{self, Record}, error::GameError, path::{asset_path, mods_path}};
+
+/// Every mod folder needs one of these to be picked up
+static MANIFEST_FILE: &str = "mod.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+  pub name: String,
+  #[serde(default)]
+  pub version: String,
+  #[serde(default)]
+  pub author: String,
+  #[serde(default)]
+  pub description: String,
+}
+
+/// A mod installed under game_folder()/mods. Its id is the name of its folder
+#[derive(Debug, Clone)]
+pub struct Mod {
+  pub id: String,
+  pub path: PathBuf,
+  pub manifest: Manifest,
+}
+
+/// Every mod in the mods folder with a readable manifest, sorted by id. Broken ones are logged and left out
+pub fn installed() -> Vec<Mod> {
+  let entries = match fs::read_dir(mods_path()) {
+    Ok(entries) => entries,
+    Err(_) => return Vec::new(),
+  };
+
+  let mut mods: Vec<Mod> = entries
+    .filter_map(|entry| {
+      let path = entry.ok()?.path();
+      let id = path.file_name()?.to_str()?.to_string();
+
+      if !path.is_dir() {
+        return None;
+      }
+
+      let manifest = fs::read_to_string(path.join(MANIFEST_FILE))
+        .map_err(GameError::from)
+        .and_then(|manifest| Ok(serde_json::from_str(&manifest)?));
+
+      match manifest {
+        Ok(manifest) => Some(Mod { id, path, manifest }),
+        Err(e) => {
+          log!("Skipping mod {}, can't read its {}: {}", id, MANIFEST_FILE, e);
+          None
+        },
+      }
+    })
+    .collect();
+
+  mods.sort_by(|a, b| a.id.cmp(&b.id));
+  mods
+}
+
+/// Mods enabled in the config, in load order. Tests never see mods
+pub fn active() -> Vec<Mod> {
+  if cfg!(test) {
+    return Vec::new();
+  }
+
+  let mut installed = installed();
+
+  read_config().mods.iter()
+    .filter_map(|id| {
+      let found = installed.iter().position(|installed| installed.id == *id).map(|i| installed.remove(i));
+
+      if found.is_none() {
+        log!("Enabled mod {} isn't installed", id);
+      }
+
+      found
+    })
+    .collect()
+}
+
+/// Stacks layers of records, each one over the ones before. A record replaces an earlier layer's record with the same
+/// first field (its id), anything new is added on the end. Returns the records with the name of the layer each came from
+pub fn layer(layers: Vec<(String, Vec<Record>)>) -> Vec<(String, Record)> {
+  let mut stacked: Vec<(String, Record)> = Vec::new();
+
+  for (name, records) in layers {
+    let mut added = 0;
+    let mut replaced = 0;
+
+    for record in records {
+      let key = record.get(0).unwrap_or_default().to_string();
+
+      // Duplicates within one file are left in for the loader to complain about
+      let existing = stacked.iter().position(|(layer, other)| *layer != name && other.get(0) == Some(key.as_str()));
+
+      match existing {
+        Some(i) => {
+          if stacked[i].0 != "base" {
+            log!("Load order conflict: {} overrides {} from {}", name, key, stacked[i].0);
+          } else {
+            log_verbose!("{} overrides {}", name, key);
+          }
+
+          stacked[i] = (name.clone(), record);
+          replaced += 1;
+        },
+        None => {
+          stacked.push((name.clone(), record));
+          added += 1;
+        },
+      }
+    }
+
+    log_verbose!("{} added {} records and replaced {}", name, added, replaced);
+  }
+
+  stacked
+}
+
+/// Reads one of the .dat assets with every active mod's copy of it layered on top
+pub fn read_layered(file: &str) -> Result<Vec<Record>, GameError> {
+  let mut layers = vec![("base".to_string(), dat::read(file)?)];
+
+  for active in active() {
+    let path = active.path.join(file);
+
+    if path.exists() {
+      let name = format!("mods/{}/{}", active.id, file);
+      layers.push((format!("mod {}", active.id), dat::parse(&name, &fs::read_to_string(path)?)?));
+    }
+  }
+
+  Ok(layer(layers).into_iter().map(|(_, record)| record).collect())
+}
+
+/// Merges every active mod's copy of a language file into the base one, key by key
+pub fn layer_language(code: &str, base: &mut Value) {
+  let Some(base) = base.as_object_mut() else { return };
+  let mut origin: HashMap<String, String> = HashMap::new();
+
+  for active in active() {
+    let path = active.path.join("lang").join(format!("{}.tr", code));
+
+    if !path.exists() {
+      continue;
+    }
+
+    let language = fs::read_to_string(&path)
+      .map_err(GameError::from)
+      .and_then(|language| Ok(serde_json::from_str::<Value>(&language)?));
+
+    let language = match language {
+      Ok(Value::Object(language)) => language,
+      Ok(_) => {
+        log!("Mod {} language {} isn't a JSON object", active.id, code);
+        continue;
+      },
+      Err(e) => {
+        log!("Failed to load mod {} language {}: {}", active.id, code, e);
+        continue;
+      },
+    };
+
+    for (key, value) in language {
+      if let Some(previous) = origin.insert(key.clone(), active.id.clone()) {
+        log!("Load order conflict: mod {} overrides language key {} from mod {}", active.id, key, previous);
+      }
+
+      base.insert(key, value);
+    }
+  }
+}
+
+/// Language codes only mods provide, so they can be offered alongside the base ones
+pub fn language_codes() -> Vec<String> {
+  active().iter()
+    .filter_map(|active| fs::read_dir(active.path.join("lang")).ok())
+    .flatten()
+    .filter_map(|entry| {
+      let path = entry.ok()?.path();
+
+      if path.extension()? != "tr" || asset_path().join("lang").join(path.file_name()?).exists() {
+        return None;
+      }
+
+      Some(path.file_stem()?.to_str()?.to_string())
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::util::dat;
+
+  use super::layer;
+
+  #[test]
+  fn mods_override_by_id_and_add_the_rest() {
+    let base = dat::parse("items.dat", "0 GOLD\n1 GEM").unwrap();
+    let first = dat::parse("a/items.dat", "1 BIG_GEM\n2 SILVER").unwrap();
+    let second = dat::parse("b/items.dat", "2 SHINY_SILVER").unwrap();
+
+    let stacked = layer(vec![("base".to_string(), base), ("a".to_string(), first), ("b".to_string(), second)]);
+    let names: Vec<(&str, &str)> = stacked.iter().map(|(layer, record)| (layer.as_str(), record.get(1).unwrap())).collect();
+
+    assert_eq!(names, vec![("base", "GOLD"), ("a", "BIG_GEM"), ("b", "SHINY_SILVER")]);
+  }
+
+  #[test]
+  fn duplicates_within_a_file_are_kept() {
+    let base = dat::parse("items.dat", "0 GOLD\n0 GEM").unwrap();
+
+    assert_eq!(layer(vec![("base".to_string(), base)]).len(), 2);
+  }
+}
@@ -1,7 +1,9 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use dirs;
 
+use super::error::GameError;
+
 pub fn is_not_installed() -> bool {
   // Check if we have a "build" folder next to the executable
   let current_exe = std::env::current_exe().unwrap();
@@ -33,6 +35,16 @@ pub fn config_path() -> PathBuf {
   game_folder().join("config.json")
 }
 
+/// Achievements and anything else kept between games
+pub fn progress_path() -> PathBuf {
+  game_folder().join("progress.json")
+}
+
+/// Each mod is a folder in here, see util::mods. Not created until the player asks to open it
+pub fn mods_path() -> PathBuf {
+  game_folder().join("mods")
+}
+
 pub fn lang_path() -> PathBuf {
   let asset_folder = asset_path();
   asset_folder.join("lang")
@@ -42,7 +54,17 @@ pub fn log_file_path() -> PathBuf {
   game_folder().join("log.txt")
 }
 
+/// Created by the first save, see util::save::save_game
+pub fn saves_path() -> PathBuf {
+  game_folder().join("saves")
+}
+
 pub fn asset_path() -> PathBuf {
+  // Test binaries live in target/*/deps, nowhere near an assets folder
+  if cfg!(test) {
+    return PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("assets");
+  }
+
   if is_not_installed() {
     let current_folder = std::env::current_dir().unwrap();
     return current_folder.join("assets");
@@ -51,4 +73,26 @@ pub fn asset_path() -> PathBuf {
   let current_exe = std::env::current_exe().unwrap();
   let exe_folder = current_exe.parent().unwrap();
   exe_folder.join("assets")
-}
\ No newline at end of file
+}
+
+/// Opens a file or folder with whatever the OS has associated with it
+pub fn open_path(path: &Path) -> Result<(), GameError> {
+  #[cfg(target_os = "windows")]
+  let mut command = std::process::Command::new("explorer");
+  #[cfg(target_os = "linux")]
+  let mut command = std::process::Command::new("xdg-open");
+  #[cfg(target_os = "macos")]
+  let mut command = std::process::Command::new("open");
+  #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+  return Err(GameError::Io(std::io::Error::new(
+    std::io::ErrorKind::Unsupported,
+    format!("Don't know how to open {} on this platform", path.display()),
+  )));
+
+  #[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
+  {
+    command.arg(path).spawn()?;
+
+    Ok(())
+  }
+}
@@ -1,32 +1,48 @@
-pub fn maybe() -> bool {
-  rand::random::<i32>() % 2 == 0
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+pub fn maybe(rng: &mut impl Rng) -> bool {
+  rng.gen_bool(0.5)
 }
 
-pub fn maybe_chance(chance: i32) -> bool {
-  rand::random::<i32>() % chance == 0
+pub fn maybe_chance(rng: &mut impl Rng, chance: i32) -> bool {
+  rng.gen_range(0..chance) == 0
 }
 
-pub fn pick_one<T>(vec: Vec<T>) -> T
+pub fn pick_one<T>(rng: &mut impl Rng, vec: Vec<T>) -> T
 where T: Clone
 {
-  vec[rand::random::<usize>() % vec.len()].clone()
+  vec[rng.gen_range(0..vec.len())].clone()
 }
 
-pub fn pick_x<T>(vec: Vec<T>, can_repeat: bool, x: i32) -> Vec<T>
+pub fn pick_x<T>(rng: &mut impl Rng, vec: Vec<T>, can_repeat: bool, x: i32) -> Vec<T>
 where T: Clone
 {
   let mut picked = Vec::new();
   let mut vec = vec.clone();
-  
+
   // Ensure there are enough items to enforce can_repeat
   if !can_repeat && vec.len() < x as usize {
     return vec;
   }
 
   for _ in 0..x {
-    let index = rand::random::<usize>() % vec.len();
+    let index = rng.gen_range(0..vec.len());
     picked.push(vec.remove(index));
   }
 
   picked
-}
\ No newline at end of file
+}
+
+/// Random seed for a new game, short enough to read out or paste into a bug report
+pub fn random_seed() -> String {
+  format!("{:016X}", rand::random::<u64>())
+}
+
+/// Turns any seed string into an RNG. Uses FNV-1a rather than std's hasher, since that one isn't stable between Rust versions
+pub fn seeded_rng(seed: impl AsRef<str>) -> StdRng {
+  let hash = seed.as_ref()
+    .bytes()
+    .fold(0xcbf29ce484222325u64, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3));
+
+  StdRng::seed_from_u64(hash)
+}
@@ -0,0 +1,220 @@
+use std::{fs, path::PathBuf};
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+use crate::{components::{core::Core, state::GameState}, log};
+
+use super::{error::GameError, migrate, path::saves_path};
+
+/// Summary of a save, stored alongside the state so the slot picker doesn't need to read the whole world
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveMeta {
+  pub slot: String,
+  pub player_name: String,
+  pub money: i32,
+  pub turn: i32,
+  /// Unix timestamp of when the save was written
+  pub timestamp: i64,
+  /// Game version (Core::version) that wrote the save
+  pub version: String,
+}
+
+/// On-disk envelope. `version` is checked and migrated (see util::migrate) before the rest is parsed
+#[derive(Deserialize)]
+pub struct SaveFile {
+  pub version: String,
+  pub meta: SaveMeta,
+  pub state: GameState,
+}
+
+/// Borrowing counterpart of SaveFile, so saving doesn't need to clone the whole state
+#[derive(Serialize)]
+struct SaveFileRef<'a> {
+  version: &'a str,
+  meta: SaveMeta,
+  state: &'a GameState,
+}
+
+/// Only used when listing saves, serde will skip over the state entirely
+#[derive(Deserialize)]
+struct SaveHeader {
+  meta: SaveMeta,
+}
+
+/// Slot names end up as file names, so only keep characters that are safe everywhere
+pub fn sanitize_slot(slot: impl AsRef<str>) -> String {
+  slot.as_ref()
+    .chars()
+    .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+    .collect()
+}
+
+/// Generate a fresh slot name for a new game, based on the player name and the current time
+pub fn new_slot_name(player_name: impl AsRef<str>) -> String {
+  sanitize_slot(format!("{}_{}", player_name.as_ref(), Local::now().format("%Y%m%d%H%M%S")))
+}
+
+pub fn slot_path(slot: impl AsRef<str>) -> PathBuf {
+  saves_path().join(format!("{}.json", sanitize_slot(slot)))
+}
+
+pub fn save_game(core: &Core) -> Result<(), GameError> {
+  let state = match &core.state {
+    Some(state) => state,
+    None => return Err(GameError::NoGame),
+  };
+
+  let slot = match &core.save_slot {
+    Some(slot) => slot,
+    None => return Err(GameError::Save("No save slot selected".to_string())),
+  };
+
+  let meta = SaveMeta {
+    slot: sanitize_slot(slot),
+    player_name: state.player.name.clone(),
+    money: state.player.money,
+    turn: state.turn,
+    timestamp: Local::now().timestamp(),
+    version: core.version.clone(),
+  };
+
+  fs::create_dir_all(saves_path())?;
+
+  // Write to a temporary file first so a crash mid-write can't corrupt an existing save
+  let path = slot_path(slot);
+  let tmp_path = path.with_extension("json.tmp");
+  let file = fs::File::create(&tmp_path)?;
+  let writer = std::io::BufWriter::new(file);
+
+  serde_json::to_writer(writer, &SaveFileRef { version: &core.version, meta, state })?;
+  fs::rename(&tmp_path, &path)?;
+
+  log!("Saved game to slot {}", slot);
+
+  Ok(())
+}
+
+/// Whether a jump from turn `from` to `to` passed an autosave point, one every `interval` turns. An interval of 0 never does
+pub fn autosave_due(interval: i32, from: i32, to: i32) -> bool {
+  interval > 0 && from / interval != to / interval
+}
+
+/// Saves the current game, logging instead of failing. Used on turn advance, where a failed save shouldn't interrupt play
+pub fn autosave(core: &Core) {
+  if let Err(e) = save_game(core) {
+    log!("Autosave failed: {}", e);
+  }
+}
+
+pub fn load_game(core: &mut Core, slot: impl AsRef<str>) -> Result<(), GameError> {
+  let slot = slot.as_ref();
+  let file = fs::File::open(slot_path(slot))?;
+  let reader = std::io::BufReader::new(file);
+  let mut doc: serde_json::Value = serde_json::from_reader(reader)?;
+
+  migrate::migrate(&mut doc, &core.version)?;
+
+  let save: SaveFile = serde_json::from_value(doc)?;
+
+  log!("Loaded slot {} (turn {}, saved with v{}, now v{})", slot, save.meta.turn, save.meta.version, save.version);
+
+  core.state = Some(save.state);
+  core.save_slot = Some(save.meta.slot);
+
+  Ok(())
+}
+
+pub fn delete_save(slot: impl AsRef<str>) -> Result<(), GameError> {
+  Ok(fs::remove_file(slot_path(slot))?)
+}
+
+/// Lists every readable save, newest first. Unreadable saves are logged and skipped
+pub fn list_saves() -> Vec<SaveMeta> {
+  let entries = match fs::read_dir(saves_path()) {
+    Ok(entries) => entries,
+    Err(e) => {
+      log!("Failed to read saves folder: {}", e);
+      return Vec::new();
+    }
+  };
+
+  let mut saves: Vec<SaveMeta> = entries
+    .filter_map(|entry| entry.ok())
+    .map(|entry| entry.path())
+    .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+    .filter_map(|path| {
+      let header = fs::File::open(&path)
+        .map(std::io::BufReader::new)
+        .map_err(serde_json::Error::io)
+        .and_then(serde_json::from_reader::<_, SaveHeader>);
+
+      match header {
+        Ok(header) => Some(header.meta),
+        Err(e) => {
+          log!("Skipping unreadable save {}: {}", path.display(), e);
+          None
+        }
+      }
+    })
+    .collect();
+
+  saves.sort_by_key(|meta| std::cmp::Reverse(meta.timestamp));
+
+  saves
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::components::{core::Core, ship::test_ship, state::GameState};
+
+  use super::{autosave_due, migrate, sanitize_slot, SaveFile, SaveFileRef, SaveMeta};
+
+  #[test]
+  fn slots_only_keep_safe_characters() {
+    assert_eq!(sanitize_slot("Jo Doe/../save-1_b"), "Jo_Doe____save-1_b");
+    assert_eq!(sanitize_slot("Zoë"), "Zo_");
+  }
+
+  #[test]
+  fn autosaves_fall_on_interval_boundaries() {
+    assert!(autosave_due(5, 4, 5));
+    assert!(autosave_due(5, 3, 12));
+    assert!(!autosave_due(5, 5, 9));
+    assert!(!autosave_due(5, 0, 4));
+    assert!(autosave_due(1, 0, 1));
+  }
+
+  #[test]
+  fn interval_zero_never_autosaves() {
+    assert!(!autosave_due(0, 0, 1));
+    assert!(!autosave_due(0, 4, 100));
+  }
+
+  #[test]
+  fn saves_round_trip() {
+    let core = Core::new();
+    let mut state = GameState::new("Test".to_string(), test_ship(), "seed".to_string());
+    state.player.money = 1234;
+    state.turn = 7;
+
+    let meta = SaveMeta {
+      slot: "test".to_string(),
+      player_name: state.player.name.clone(),
+      money: state.player.money,
+      turn: state.turn,
+      timestamp: 0,
+      version: core.version.clone(),
+    };
+
+    let mut doc = serde_json::to_value(SaveFileRef { version: &core.version, meta, state: &state }).unwrap();
+    migrate::migrate(&mut doc, &core.version).unwrap();
+    let save: SaveFile = serde_json::from_value(doc).unwrap();
+
+    assert_eq!(save.meta.turn, 7);
+    assert_eq!(save.state.player.name, "Test");
+    assert_eq!(save.state.player.money, 1234);
+    assert_eq!(save.state.planets.len(), state.planets.len());
+    assert_eq!(save.state.seed, state.seed);
+  }
+}